@@ -0,0 +1,63 @@
+//! Atomic module
+//!
+//! Contains `write` routine which replaces file atomically through temporary file
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process;
+
+/// Write file by `write_contents` to temporary file in the same directory and rename it over `path`,
+/// so that reader sees either old or new file but never partially written one.
+///
+/// Permissions of existing file are kept. If `sync` is enabled, data is flushed to disk before rename.
+pub fn write<F>(path: &Path, sync: bool, write_contents: F) -> Result<(), io::Error>
+where
+    F: FnOnce(&mut BufWriter<&File>) -> Result<(), io::Error>,
+{
+    let name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path is not a file"))?;
+    let mut temp_name = name.to_os_string();
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp_path = path.with_file_name(temp_name);
+    let result = write_temp(path, &temp_path, sync, write_contents).and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_temp<F>(path: &Path, temp_path: &Path, sync: bool, write_contents: F) -> Result<(), io::Error>
+where
+    F: FnOnce(&mut BufWriter<&File>) -> Result<(), io::Error>,
+{
+    let file = File::create(temp_path)?;
+    let mut writer = BufWriter::new(&file);
+    write_contents(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+    if let Ok(metadata) = fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    if sync {
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replace() -> Result<(), io::Error> {
+        let path = std::env::temp_dir().join(format!("tini-atomic-{}.ini", process::id()));
+        fs::write(&path, "old")?;
+        let failed = write(&path, false, |w| w.write_all(b"partial").and(Err(io::ErrorKind::Other.into())));
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path)?, "old");
+        write(&path, true, |w| w.write_all(b"new"))?;
+        let result = fs::read_to_string(&path);
+        fs::remove_file(&path)?;
+        assert_eq!(result?, "new");
+        Ok(())
+    }
+}
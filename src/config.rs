@@ -0,0 +1,810 @@
+//! Parser configuration module
+//!
+//! Contains [ParserConfig] with options which control how ini-files are parsed
+use crate::{Encoding, Error, Ini, IniEvents, ParseError, ParseErrorKind, ParseIssue};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Action on key which is already defined in the same section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// keep the first value and ignore the following ones
+    KeepFirst,
+    /// replace the value with the last one
+    #[default]
+    KeepLast,
+    /// stop parsing with [ParseError::DuplicateKey](crate::ParseErrorKind::DuplicateKey)
+    Error,
+    /// join all values with `, ` separator, so they can be read with [`get_vec()`](Ini::get_vec)
+    CollectIntoVec,
+}
+
+/// Action on section header which already appeared in document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateSectionPolicy {
+    /// add keys to the earlier section
+    #[default]
+    Merge,
+    /// drop keys of the earlier section, section keeps its position in document
+    Replace,
+    /// stop parsing with [ParseError::DuplicateSection](crate::ParseErrorKind::DuplicateSection)
+    Error,
+}
+
+/// Key-value pair passed to hook set with [`ParserConfig::on_entry()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedEntry<'a> {
+    /// name of section containing key
+    pub section: &'a str,
+    /// name of key
+    pub key: &'a str,
+    /// value of key
+    pub value: &'a str,
+    /// number of line starting from 1
+    pub line: usize,
+    /// `true` if key is already defined in section
+    pub duplicate: bool,
+}
+
+/// Decision of hook set with [`ParserConfig::on_entry()`] about key-value pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryAction {
+    /// add key-value pair as is
+    Keep,
+    /// add given key and value instead
+    Replace(String, String),
+    /// ignore key-value pair
+    Skip,
+    /// stop parsing with [Rejected](crate::ParseErrorKind::Rejected) error
+    Reject,
+}
+
+/// Decision of hook set with [`ParserConfig::on_section()`] about section header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionAction {
+    /// add section as is
+    Keep,
+    /// add keys into section with given name instead
+    Rename(String),
+    /// ignore section header and keys up to the next header
+    Skip,
+    /// stop parsing with [Rejected](crate::ParseErrorKind::Rejected) error
+    Reject,
+}
+
+/// Callback invoked during parsing, shared between clones of configuration
+pub(crate) struct Hook<F: ?Sized>(pub(crate) Arc<F>);
+
+impl<F: ?Sized> Clone for Hook<F> {
+    fn clone(&self) -> Self {
+        Hook(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Hook<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Hook")
+    }
+}
+
+pub(crate) type EntryHook = Hook<dyn Fn(&ParsedEntry) -> EntryAction + Send + Sync>;
+pub(crate) type SectionHook = Hook<dyn Fn(&str) -> SectionAction + Send + Sync>;
+
+/// Set of options used to parse ini-files
+///
+/// Use [`Ini::builder()`](Ini::builder) or [`ParserConfig::default()`] to get default configuration,
+/// change options with method chaining and finish with [`parse_str()`](ParserConfig::parse_str),
+/// [`parse_reader()`](ParserConfig::parse_reader) or [`parse_file()`](ParserConfig::parse_file).
+/// Configuration is not consumed by parsing, so it can be reused for many files.
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let conf = Ini::builder()
+///     .comment_prefixes(["//"])
+///     .inline_comments(false)
+///     .parse_str("// settings\n[section]\nurl = http://example.com")
+///     .unwrap();
+///
+/// let url: Option<String> = conf.get("section", "url");
+/// assert_eq!(url, Some("http://example.com".to_owned()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParserConfig {
+    pub(crate) comment_prefixes: Vec<String>,
+    pub(crate) inline_comments: bool,
+    pub(crate) line_continuation: bool,
+    pub(crate) multiline_values: bool,
+    pub(crate) quoted_values: bool,
+    pub(crate) global_section: String,
+    pub(crate) default_section: Option<String>,
+    duplicate_keys: Option<DuplicateKeyPolicy>,
+    duplicate_sections: Option<DuplicateSectionPolicy>,
+    pub(crate) delimiters: Vec<char>,
+    pub(crate) case_insensitive: bool,
+    pub(crate) strict: bool,
+    pub(crate) allow_bare_keys: bool,
+    pub(crate) array_keys: bool,
+    pub(crate) trim_values: bool,
+    pub(crate) unicode_escapes: bool,
+    pub(crate) max_input_size: Option<usize>,
+    pub(crate) max_line_length: Option<usize>,
+    pub(crate) max_sections: Option<usize>,
+    pub(crate) max_keys: Option<usize>,
+    pub(crate) entry_hook: Option<EntryHook>,
+    pub(crate) section_hook: Option<SectionHook>,
+}
+
+impl ParserConfig {
+    /// Create default configuration (similar to [ParserConfig::default])
+    pub fn new() -> ParserConfig {
+        ParserConfig {
+            comment_prefixes: vec![";".to_owned(), "#".to_owned()],
+            inline_comments: true,
+            line_continuation: false,
+            multiline_values: false,
+            quoted_values: false,
+            global_section: String::new(),
+            default_section: None,
+            duplicate_keys: None,
+            duplicate_sections: None,
+            delimiters: vec!['='],
+            case_insensitive: false,
+            strict: false,
+            allow_bare_keys: false,
+            array_keys: false,
+            trim_values: true,
+            unicode_escapes: false,
+            max_input_size: None,
+            max_line_length: None,
+            max_sections: None,
+            max_keys: None,
+            entry_hook: None,
+            section_hook: None,
+        }
+    }
+
+    /// Set strings which start a comment (`;` and `#` by default)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().comment_prefixes(["%", "--"]).parse_str("[a]\nb = 1 -- one\nc = #2 % two").unwrap();
+    ///
+    /// let b: Option<u8> = conf.get("a", "b");
+    /// let c: Option<String> = conf.get("a", "c");
+    ///
+    /// assert_eq!(b, Some(1));
+    /// assert_eq!(c, Some("#2".to_owned()));
+    /// ```
+    pub fn comment_prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.comment_prefixes = prefixes.into_iter().map(|s| s.as_ref().to_owned()).filter(|s| !s.is_empty()).collect();
+        self
+    }
+
+    /// Set characters which start a comment, each character is a separate prefix
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().comment_chars("!").parse_str("! comment\n[a]\nb = #1").unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// assert_eq!(b, Some("#1".to_owned()));
+    /// ```
+    pub fn comment_chars(self, chars: &str) -> Self {
+        self.comment_prefixes(chars.chars().map(String::from).collect::<Vec<_>>())
+    }
+
+    /// Enable or disable comments at the end of line (enabled by default)
+    ///
+    /// When disabled, only lines starting with comment prefix are treated as comments.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().inline_comments(false).parse_str("; comment\n[a]\nb = 1 ; 2").unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// assert_eq!(b, Some("1 ; 2".to_owned()));
+    /// ```
+    pub fn inline_comments(mut self, enabled: bool) -> Self {
+        self.inline_comments = enabled;
+        self
+    }
+
+    /// Enable or disable joining of lines ended with backslash (disabled by default)
+    ///
+    /// Backslash is removed, leading whitespace of the next line is skipped and both lines are joined into one.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().line_continuation(true).parse_str("[a]\nb = long \\\n    value").unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// assert_eq!(b, Some("long value".to_owned()));
+    /// ```
+    pub fn line_continuation(mut self, enabled: bool) -> Self {
+        self.line_continuation = enabled;
+        self
+    }
+
+    /// Enable or disable python-style multi-line values (disabled by default)
+    ///
+    /// Lines indented deeper than the key are appended to its value with a newline.
+    /// Blank line ends the value, comment lines are skipped. Empty first line is not
+    /// kept in the value, so `key =` followed by indented lines doesn't start with newline.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().multiline_values(true).parse_str("[a]\nb = one\n  two\n  three\nc = 4").unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// let c: Option<u8> = conf.get("a", "c");
+    /// assert_eq!(b, Some("one\ntwo\nthree".to_owned()));
+    /// assert_eq!(c, Some(4));
+    /// ```
+    pub fn multiline_values(mut self, enabled: bool) -> Self {
+        self.multiline_values = enabled;
+        self
+    }
+
+    /// Enable or disable quoted values (disabled by default)
+    ///
+    /// Quotes around values wrapped in `"` or `'` are stripped and escape sequences
+    /// (`\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'` and `\uXXXX`) are decoded.
+    /// Comment prefixes inside quotes don't start a comment.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().quoted_values(true).parse_str(r#"[a]
+    /// b = "  one; two\tthree "  ; comment
+    /// c = 'single \u0041'"#).unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// let c: Option<String> = conf.get("a", "c");
+    /// assert_eq!(b, Some("  one; two\tthree ".to_owned()));
+    /// assert_eq!(c, Some("single A".to_owned()));
+    /// ```
+    pub fn quoted_values(mut self, enabled: bool) -> Self {
+        self.quoted_values = enabled;
+        self
+    }
+
+    /// Set name of pseudo-section for keys placed before the first section header (empty string by default)
+    ///
+    /// Keys of this section are written before the first section header on output.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().global_section("DEFAULT").parse_str("a = 1\n[b]\nc = 2").unwrap();
+    ///
+    /// let a: Option<u8> = conf.get("DEFAULT", "a");
+    /// assert_eq!(a, Some(1));
+    /// assert_eq!(conf.to_string(), "a = 1\n\n[b]\nc = 2\n");
+    /// ```
+    pub fn global_section<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.global_section = name.into();
+        self
+    }
+
+    /// Set section used as fallback for keys missing in other sections (disabled by default),
+    /// see [`Ini::default_section()`](Ini::default_section)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let input = "[DEFAULT]\nuser = admin\nport = 22\n[web]\nport = 80\n[db]";
+    /// let conf = Ini::builder().default_section("DEFAULT").parse_str(input).unwrap();
+    ///
+    /// assert_eq!(conf.get_raw("web", "user"), Some("admin"));
+    /// assert_eq!(conf.get::<u16>("web", "port"), Some(80));
+    /// assert_eq!(conf.get::<u16>("db", "port"), Some(22));
+    /// ```
+    pub fn default_section<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.default_section = Some(name.into());
+        self
+    }
+
+    /// Set action on duplicate keys in section ([DuplicateKeyPolicy::KeepLast] by default)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{DuplicateKeyPolicy, Ini};
+    /// let input = "[unit]\nafter = network.target\nafter = sound.target";
+    ///
+    /// let conf = Ini::builder().duplicate_keys(DuplicateKeyPolicy::CollectIntoVec).parse_str(input).unwrap();
+    /// let after: Option<Vec<String>> = conf.get_vec("unit", "after");
+    /// assert_eq!(after, Some(vec!["network.target".to_owned(), "sound.target".to_owned()]));
+    ///
+    /// let conf = Ini::builder().duplicate_keys(DuplicateKeyPolicy::KeepFirst).parse_str(input).unwrap();
+    /// let after: Option<String> = conf.get("unit", "after");
+    /// assert_eq!(after.as_deref(), Some("network.target"));
+    ///
+    /// assert!(Ini::builder().duplicate_keys(DuplicateKeyPolicy::Error).parse_str(input).is_err());
+    /// ```
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = Some(policy);
+        self
+    }
+
+    /// Set action on repeated section headers ([DuplicateSectionPolicy::Merge] by default)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{DuplicateSectionPolicy, Ini};
+    /// let input = "[a]\nx = 1\ny = 2\n[b]\nz = 3\n[a]\nx = 4";
+    ///
+    /// let conf = Ini::builder().parse_str(input).unwrap();
+    /// assert_eq!(conf.to_string(), "[a]\nx = 4\ny = 2\n\n[b]\nz = 3\n");
+    ///
+    /// let conf = Ini::builder().duplicate_sections(DuplicateSectionPolicy::Replace).parse_str(input).unwrap();
+    /// assert_eq!(conf.to_string(), "[a]\nx = 4\n\n[b]\nz = 3\n");
+    ///
+    /// assert!(Ini::builder().duplicate_sections(DuplicateSectionPolicy::Error).parse_str(input).is_err());
+    /// ```
+    pub fn duplicate_sections(mut self, policy: DuplicateSectionPolicy) -> Self {
+        self.duplicate_sections = Some(policy);
+        self
+    }
+
+    /// Enable or disable `:` as key-value delimiter in addition to `=` (disabled by default)
+    ///
+    /// The first delimiter in line separates key from value.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().colon_delimiter(true).parse_str("[a]\nb: 1\nc = 2").unwrap();
+    ///
+    /// let b: Option<u8> = conf.get("a", "b");
+    /// let c: Option<u8> = conf.get("a", "c");
+    /// assert_eq!(b, Some(1));
+    /// assert_eq!(c, Some(2));
+    /// ```
+    pub fn colon_delimiter(mut self, enabled: bool) -> Self {
+        self.delimiters.retain(|&c| c != ':');
+        if enabled {
+            self.delimiters.push(':');
+        }
+        self
+    }
+
+    /// Set characters which separate key from value (`=` by default)
+    ///
+    /// The first delimiter found in line wins, so the value may contain any delimiter characters,
+    /// but the key can't. Empty set of delimiters falls back to `=`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().delimiters(":").parse_str("[a]\nkey=name: value=1").unwrap();
+    /// let value: Option<String> = conf.get("a", "key=name");
+    /// assert_eq!(value.as_deref(), Some("value=1"));
+    ///
+    /// let conf = Ini::builder().delimiters("=:").parse_str("[a]\nkey=name: value=1").unwrap();
+    /// let value: Option<String> = conf.get("a", "key");
+    /// assert_eq!(value.as_deref(), Some("name: value=1"));
+    ///
+    /// let conf = Ini::builder().delimiters("\t").parse_str("[a]\nkey\tvalue = 1").unwrap();
+    /// let value: Option<String> = conf.get("a", "key");
+    /// assert_eq!(value.as_deref(), Some("value = 1"));
+    /// ```
+    pub fn delimiters(mut self, chars: &str) -> Self {
+        self.delimiters = chars.chars().collect();
+        if self.delimiters.is_empty() {
+            self.delimiters.push('=');
+        }
+        self
+    }
+
+    /// Enable or disable case-insensitive section and key names (disabled by default)
+    ///
+    /// Names are converted to lower case on parse, on insertion and on lookup,
+    /// so the document is written with lower case names.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().case_insensitive(true).parse_str("[Server]\nHost = localhost").unwrap();
+    ///
+    /// let host: Option<String> = conf.get("SERVER", "host");
+    /// assert_eq!(host.as_deref(), Some("localhost"));
+    /// assert_eq!(conf.to_string(), "[server]\nhost = localhost\n");
+    /// ```
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// Allow or disallow keys without values (disallowed by default)
+    ///
+    /// Allowed line without delimiter is parsed as key with empty value instead of
+    /// [IncorrectSyntax](crate::ParseErrorKind::IncorrectSyntax) error.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().allow_bare_keys(true).parse_str("[player]\nverbose\nvolume = 50").unwrap();
+    ///
+    /// assert!(conf.has_key("player", "verbose"));
+    /// assert!(!conf.has_key("player", "fullscreen"));
+    /// ```
+    pub fn allow_bare_keys(mut self, allowed: bool) -> Self {
+        self.allow_bare_keys = allowed;
+        self
+    }
+
+    /// Enable or disable trimming of whitespace around values (enabled by default)
+    ///
+    /// Disabled trimming keeps all whitespace between delimiter and inline comment or end of line,
+    /// including space after delimiter. Values in quotes (see [`quoted_values()`](ParserConfig::quoted_values))
+    /// keep whitespace inside quotes regardless of this option.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().trim_values(false).parse_str("[format]\nindent =    \nprefix=> ").unwrap();
+    ///
+    /// let indent: Option<String> = conf.get("format", "indent");
+    /// assert_eq!(indent.as_deref(), Some("    "));
+    /// let prefix: Option<String> = conf.get("format", "prefix");
+    /// assert_eq!(prefix.as_deref(), Some("> "));
+    /// ```
+    pub fn trim_values(mut self, enabled: bool) -> Self {
+        self.trim_values = enabled;
+        self
+    }
+
+    /// Enable or disable decoding of `\uXXXX` sequences in names, values and comments (disabled by default)
+    ///
+    /// Characters outside of Basic Multilingual Plane are decoded from UTF-16 surrogate pairs,
+    /// as they are written by [`FormatOptions::escape_non_ascii()`](crate::FormatOptions::escape_non_ascii).
+    /// Other backslashes are kept as is.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().unicode_escapes(true).parse_str("[caf\\u00e9]\nname = Jos\\u00e9").unwrap();
+    ///
+    /// let name: Option<String> = conf.get("café", "name");
+    /// assert_eq!(name.as_deref(), Some("José"));
+    /// ```
+    pub fn unicode_escapes(mut self, enabled: bool) -> Self {
+        self.unicode_escapes = enabled;
+        self
+    }
+
+    /// Enable or disable php-style array keys (disabled by default)
+    ///
+    /// Values of keys ended with `[]` are collected into list under the key name without brackets
+    /// regardless of [`duplicate_keys()`](ParserConfig::duplicate_keys) policy, so they can be read with
+    /// [`get_vec()`](Ini::get_vec). To collect values of plain repeated keys use [DuplicateKeyPolicy::CollectIntoVec].
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().array_keys(true).parse_str("[a]\nport[] = 80\nport[] = 443").unwrap();
+    ///
+    /// let ports: Option<Vec<u16>> = conf.get_vec("a", "port");
+    /// assert_eq!(ports, Some(vec![80, 443]));
+    /// ```
+    pub fn array_keys(mut self, enabled: bool) -> Self {
+        self.array_keys = enabled;
+        self
+    }
+
+    /// Enable or disable strict parsing (disabled by default)
+    ///
+    /// Strict parser returns an error instead of ignoring or fixing suspicious input:
+    /// - [KeyOutsideSection](crate::ParseErrorKind::KeyOutsideSection) for keys before the first section header;
+    /// - [IncorrectSection](crate::ParseErrorKind::IncorrectSection) for empty section names and extra brackets,
+    ///   like `[]` or `[[section]]`;
+    /// - [IncorrectSyntax](crate::ParseErrorKind::IncorrectSyntax) for unterminated quoted values
+    ///   if [`quoted_values`](ParserConfig::quoted_values) is enabled.
+    ///
+    /// Strict mode also uses [DuplicateKeyPolicy::Error] and [DuplicateSectionPolicy::Error] instead of default
+    /// policies. Policies set with [`duplicate_keys()`](ParserConfig::duplicate_keys) and
+    /// [`duplicate_sections()`](ParserConfig::duplicate_sections) take precedence regardless of call order.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Error, Ini, ParseErrorKind};
+    /// let config = Ini::builder().strict(true);
+    ///
+    /// assert!(config.parse_str("[a]\nb = 1").is_ok());
+    ///
+    /// match config.parse_str("b = 1\n[a]") {
+    ///     Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::KeyOutsideSection, 1)),
+    ///     _ => unreachable!(),
+    /// }
+    /// match config.parse_str("[a]\n[b]\n[a]") {
+    ///     Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::DuplicateSection, 3)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// Private method which returns policy for duplicate keys, set explicitly or implied by strict mode
+    pub(crate) fn duplicate_key_policy(&self) -> DuplicateKeyPolicy {
+        let implied = if self.strict { DuplicateKeyPolicy::Error } else { DuplicateKeyPolicy::KeepLast };
+        self.duplicate_keys.unwrap_or(implied)
+    }
+
+    /// Private method which returns policy for duplicate sections, set explicitly or implied by strict mode
+    pub(crate) fn duplicate_section_policy(&self) -> DuplicateSectionPolicy {
+        let implied = if self.strict { DuplicateSectionPolicy::Error } else { DuplicateSectionPolicy::Merge };
+        self.duplicate_sections.unwrap_or(implied)
+    }
+
+    /// Limit size of input in bytes (unlimited by default)
+    ///
+    /// Larger input is rejected with [InputTooLarge](crate::ParseErrorKind::InputTooLarge) error before parsing,
+    /// and [`parse_reader()`](ParserConfig::parse_reader) reads no more than one byte over the limit.
+    /// Together with the other limits it protects from memory exhaustion on untrusted input.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Error, Ini, ParseErrorKind};
+    /// let config = Ini::builder().max_input_size(16);
+    ///
+    /// assert!(config.parse_str("[a]\nb = 1").is_ok());
+    /// match config.parse_reader(&mut "[a]\nb = 1\nc = 2\nd = 3".as_bytes()) {
+    ///     Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::InputTooLarge, 4)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn max_input_size(mut self, bytes: usize) -> Self {
+        self.max_input_size = Some(bytes);
+        self
+    }
+
+    /// Limit length of line in bytes (unlimited by default)
+    ///
+    /// Longer line is rejected with [LineTooLong](crate::ParseErrorKind::LineTooLong) error.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let config = Ini::builder().max_line_length(8);
+    ///
+    /// assert!(config.parse_str("[a]\nb = 1").is_ok());
+    /// assert!(config.parse_str("[a]\nb = 123456").is_err());
+    /// ```
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.max_line_length = Some(bytes);
+        self
+    }
+
+    /// Limit number of distinct sections (unlimited by default)
+    ///
+    /// Extra section is rejected with [TooManySections](crate::ParseErrorKind::TooManySections) error.
+    /// Keys before the first section header are not counted as a section.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let config = Ini::builder().max_sections(2);
+    ///
+    /// assert!(config.parse_str("[a]\n[b]\n[a]").is_ok());
+    /// assert!(config.parse_str("[a]\n[b]\n[c]").is_err());
+    /// ```
+    pub fn max_sections(mut self, count: usize) -> Self {
+        self.max_sections = Some(count);
+        self
+    }
+
+    /// Limit total number of key-value lines in all sections (unlimited by default)
+    ///
+    /// Extra key is rejected with [TooManyKeys](crate::ParseErrorKind::TooManyKeys) error.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let config = Ini::builder().max_keys(2);
+    ///
+    /// assert!(config.parse_str("[a]\nb = 1\n[c]\nd = 2").is_ok());
+    /// assert!(config.parse_str("[a]\nb = 1\nb = 2\nb = 3").is_err());
+    /// ```
+    pub fn max_keys(mut self, count: usize) -> Self {
+        self.max_keys = Some(count);
+        self
+    }
+
+    /// Call `hook` for every key-value pair to keep, replace, skip or reject it (no hook by default)
+    ///
+    /// Hook gets key after normalization of case and `[]` suffix, and [ParsedEntry::duplicate] tells if
+    /// key is already defined in section. Replaced entry is handled by [`duplicate_keys()`](ParserConfig::duplicate_keys)
+    /// policy like original one. Hooks don't apply to [`events()`](ParserConfig::events).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{EntryAction, Ini};
+    /// let config = Ini::builder().on_entry(|entry| match entry.key {
+    ///     "password" => EntryAction::Replace("password".to_owned(), "***".to_owned()),
+    ///     "old_name" => EntryAction::Replace("name".to_owned(), entry.value.to_owned()),
+    ///     "debug" if entry.section == "release" => EntryAction::Reject,
+    ///     _ if entry.duplicate => EntryAction::Skip,
+    ///     _ => EntryAction::Keep,
+    /// });
+    ///
+    /// let conf = config.parse_str("[db]\nold_name = app\npassword = secret\nname = other").unwrap();
+    /// assert_eq!(conf.to_string(), "[db]\nname = app\npassword = ***\n");
+    /// assert!(config.parse_str("[release]\ndebug = true").is_err());
+    /// ```
+    pub fn on_entry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ParsedEntry) -> EntryAction + Send + Sync + 'static,
+    {
+        self.entry_hook = Some(Hook(Arc::new(hook)));
+        self
+    }
+
+    /// Call `hook` for every section header to keep, rename, skip or reject section (no hook by default)
+    ///
+    /// Hook gets section name after normalization of case. Keys before the first header are not affected.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, SectionAction};
+    /// let config = Ini::builder().on_section(|name| match name {
+    ///     "server" | "client" => SectionAction::Keep,
+    ///     "srv" => SectionAction::Rename("server".to_owned()),
+    ///     "internal" => SectionAction::Skip,
+    ///     _ => SectionAction::Reject,
+    /// });
+    ///
+    /// let conf = config.parse_str("[srv]\nport = 80\n[internal]\ntoken = x\n[client]").unwrap();
+    /// assert_eq!(conf.to_string(), "[server]\nport = 80\n\n[client]\n");
+    /// assert!(config.parse_str("[unknown]").is_err());
+    /// ```
+    pub fn on_section<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) -> SectionAction + Send + Sync + 'static,
+    {
+        self.section_hook = Some(Hook(Arc::new(hook)));
+        self
+    }
+
+    /// Check that input is not larger than [`max_input_size()`](ParserConfig::max_input_size),
+    /// error points to the line where limit is exceeded
+    pub(crate) fn check_input_size(&self, bytes: &[u8]) -> Result<(), ParseError> {
+        let max = match self.max_input_size {
+            Some(max) if bytes.len() > max => max,
+            _ => return Ok(()),
+        };
+        let start = bytes[..max].iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let end = bytes[max..].iter().position(|&b| b == b'\n').map_or(bytes.len(), |i| max + i);
+        let line = bytes[..start].iter().filter(|&&b| b == b'\n').count() + 1;
+        let text = String::from_utf8_lossy(&bytes[start..end]);
+        Err(ParseError::new(ParseErrorKind::InputTooLarge, line, text.trim_end_matches('\r'), 0).shifted(start))
+    }
+
+    /// Parse string with current configuration
+    ///
+    /// # Errors
+    /// This function will return an [Error] if buffer cannot be parsed
+    pub fn parse_str(&self, buf: &str) -> Result<Ini, Error> {
+        Ini::parse(buf, self)
+    }
+
+    /// Parse string with current configuration, collecting problems instead of stopping on the first one
+    ///
+    /// See [Ini::parse_lossy] for details.
+    pub fn parse_lossy(&self, buf: &str) -> (Ini, Vec<ParseIssue>) {
+        let mut issues = Vec::new();
+        let ini = Ini::parse_with_issues(buf, self, Some(&mut issues)).unwrap_or_default();
+        (ini, issues)
+    }
+
+    /// Create [IniEvents] streaming parser over string with current configuration
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Event, Ini};
+    /// let mut events = Ini::builder().case_insensitive(true).events("[Main]");
+    ///
+    /// assert_eq!(events.next(), Some(Event::SectionStart { name: "main".to_owned(), span: 0..6 }));
+    /// ```
+    pub fn events<'a>(&self, buf: &'a str) -> IniEvents<'a> {
+        IniEvents::with_config(buf, self)
+    }
+
+    /// Parse data from any struct who implement [Read] trait with current configuration
+    ///
+    /// # Errors
+    /// This function will return an [Error] if reader cannot be read or parsed
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut reader = "[section]\nitem: value".as_bytes();
+    ///
+    /// let conf = Ini::builder().colon_delimiter(true).parse_reader(&mut reader).unwrap();
+    ///
+    /// let item: Option<String> = conf.get("section", "item");
+    /// assert_eq!(item.as_deref(), Some("value"));
+    /// ```
+    pub fn parse_reader<R>(&self, reader: &mut R) -> Result<Ini, Error>
+    where
+        R: Read,
+    {
+        let mut buffer = Vec::new();
+        match self.max_input_size {
+            Some(max) => reader.take(max as u64 + 1).read_to_end(&mut buffer)?,
+            None => reader.read_to_end(&mut buffer)?,
+        };
+        self.check_input_size(&buffer)?;
+        let buffer = String::from_utf8(buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.parse_str(&buffer)
+    }
+
+    /// Parse bytes in given encoding with current configuration
+    ///
+    /// # Errors
+    /// This function will return an [Error] if bytes cannot be decoded or parsed
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Encoding, Ini};
+    /// let bytes = b"\xff\xfe[\x00a\x00]\x00\n\x00b\x00:\x001\x00";
+    ///
+    /// let conf = Ini::builder().colon_delimiter(true).parse_bytes(bytes, Encoding::detect(bytes)).unwrap();
+    ///
+    /// let b: Option<u8> = conf.get("a", "b");
+    /// assert_eq!(b, Some(1));
+    /// ```
+    pub fn parse_bytes(&self, bytes: &[u8], encoding: Encoding) -> Result<Ini, Error> {
+        self.check_input_size(bytes)?;
+        self.parse_str(&encoding.decode(bytes)?)
+    }
+
+    /// Parse file with current configuration
+    ///
+    /// # Errors
+    /// This function will return an [Error] if file cannot be opened or parsed
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().case_insensitive(true).parse_file("example.ini");
+    ///
+    /// assert!(conf.ok().is_some());
+    /// ```
+    pub fn parse_file<S>(&self, path: &S) -> Result<Ini, Error>
+    where
+        S: AsRef<Path> + ?Sized,
+    {
+        let file = File::open(path)?;
+        let mut reader = BufReader::new(file);
+        let mut result = self.parse_reader(&mut reader)?;
+        result.source = Some(path.as_ref().to_owned());
+        Ok(result)
+    }
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
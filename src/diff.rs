@@ -0,0 +1,127 @@
+//! Diff module
+//!
+//! Contains [IniDiff] with changes between two documents found by [`Ini::diff()`](crate::Ini::diff)
+use crate::Ini;
+use std::fmt;
+
+/// Single change of section or key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// section is present only in the new document
+    SectionAdded(String),
+    /// section is present only in the old document
+    SectionRemoved(String),
+    /// key is present only in the new document
+    KeyAdded { section: String, key: String, value: String },
+    /// key is present only in the old document
+    KeyRemoved { section: String, key: String, value: String },
+    /// key has different values in documents
+    KeyChanged { section: String, key: String, old: String, new: String },
+}
+
+/// Changes between two documents
+///
+/// Changes are listed in order of sections and keys in the old document followed by sections and keys
+/// which were added in the new one. Keys of added and removed sections are listed after the section change.
+/// [Display](#impl-Display-for-IniDiff) writes one change per line: `+` for added, `-` for removed
+/// and `~` for changed items.
+///
+/// # Example
+/// ```
+/// # use tini::{Change, Ini};
+/// let old = Ini::from_string("[ui]\ntheme = light\nfont = mono\n[net]\nproxy = none").unwrap();
+/// let new = Ini::from_string("[ui]\ntheme = dark\nsize = 12").unwrap();
+///
+/// let diff = old.diff(&new);
+/// assert_eq!(diff.changes()[0], Change::KeyChanged {
+///     section: "ui".to_owned(),
+///     key: "theme".to_owned(),
+///     old: "light".to_owned(),
+///     new: "dark".to_owned(),
+/// });
+/// assert_eq!(diff.to_string(), ["~ [ui] theme = light -> dark",
+///                               "- [ui] font = mono",
+///                               "+ [ui] size = 12",
+///                               "- [net]",
+///                               "- [net] proxy = none"].join("\n"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IniDiff {
+    changes: Vec<Change>,
+}
+
+impl IniDiff {
+    /// Compare documents `old` and `new`
+    pub(crate) fn new(old: &Ini, new: &Ini) -> IniDiff {
+        let mut changes = Vec::new();
+        for (name, section) in &old.document {
+            match new.document.get(name) {
+                Some(other) => {
+                    for (key, value) in &section.values {
+                        let (section, key) = (name.clone(), key.clone());
+                        match other.values.get(&key) {
+                            None => changes.push(Change::KeyRemoved { section, key, value: value.clone() }),
+                            Some(new) if new != value => {
+                                changes.push(Change::KeyChanged { section, key, old: value.clone(), new: new.clone() })
+                            }
+                            Some(_) => (),
+                        }
+                    }
+                    for (key, value) in other.values.iter().filter(|(key, _)| !section.values.contains_key(*key)) {
+                        let (section, key, value) = (name.clone(), key.clone(), value.clone());
+                        changes.push(Change::KeyAdded { section, key, value });
+                    }
+                }
+                None => {
+                    changes.push(Change::SectionRemoved(name.clone()));
+                    for (key, value) in &section.values {
+                        let (section, key, value) = (name.clone(), key.clone(), value.clone());
+                        changes.push(Change::KeyRemoved { section, key, value });
+                    }
+                }
+            }
+        }
+        for (name, section) in new.document.iter().filter(|(name, _)| !old.document.contains_key(*name)) {
+            changes.push(Change::SectionAdded(name.clone()));
+            for (key, value) in &section.values {
+                let (section, key, value) = (name.clone(), key.clone(), value.clone());
+                changes.push(Change::KeyAdded { section, key, value });
+            }
+        }
+        IniDiff { changes }
+    }
+
+    /// List of changes
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Check if documents have the same sections, keys and values
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::SectionAdded(name) => write!(f, "+ [{}]", name),
+            Change::SectionRemoved(name) => write!(f, "- [{}]", name),
+            Change::KeyAdded { section, key, value } => write!(f, "+ [{}] {} = {}", section, key, value),
+            Change::KeyRemoved { section, key, value } => write!(f, "- [{}] {} = {}", section, key, value),
+            Change::KeyChanged { section, key, old, new } => write!(f, "~ [{}] {} = {} -> {}", section, key, old, new),
+        }
+    }
+}
+
+impl fmt::Display for IniDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, change) in self.changes.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+//! Document module
+//!
+//! Contains [Document] type which keeps ini-file exactly as it was read
+use crate::atomic;
+use crate::config::ParserConfig;
+use crate::error::{Error, ValidationError};
+use crate::escape::{needs_quotes, quote};
+use crate::format::LineEnding;
+use crate::parser::{parse_line, split_comment, Parsed};
+use crate::{FromIniValue, ToIniValue};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Parsed meaning of line
+#[derive(Debug, Clone)]
+enum Kind {
+    /// blank line, comment or anything else which is kept as is
+    Other,
+    /// section header with section name
+    Section(String),
+    /// key-value pair with key, parsed value and position of raw value in line
+    Value(String, String, Range<usize>),
+}
+
+/// Single line of document with its line break
+#[derive(Debug, Clone)]
+struct Line {
+    raw: String,
+    kind: Kind,
+}
+
+/// Lossless ini-document
+///
+/// Unlike [Ini](crate::Ini), document stores all lines of input including comments, blank lines and formatting,
+/// so unmodified document is written back byte-for-byte. Changes affect only lines of modified keys.
+///
+/// Line continuation and multi-line values are not supported in this mode, every line is parsed on its own.
+///
+/// # Example
+/// ```
+/// # use tini::Document;
+/// let input = "; server settings\r\n[server]\r\nhost   =  localhost ; local only\r\n\r\nport = 80\r\n";
+/// let mut doc = Document::from_string(input).unwrap();
+///
+/// assert_eq!(doc.to_string(), input);
+///
+/// let port: Option<u16> = doc.get("server", "port");
+/// assert_eq!(port, Some(80));
+///
+/// doc.set("server", "host", "0.0.0.0").unwrap();
+/// doc.set("server", "timeout", 30).unwrap();
+/// assert_eq!(doc.to_string(),
+///            "; server settings\r\n[server]\r\nhost   =  0.0.0.0 ; local only\r\n\r\nport = 80\r\ntimeout = 30\r\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    lines: Vec<Line>,
+    config: ParserConfig,
+    line_ending: LineEnding,
+}
+
+impl Document {
+    /// Construct document from string with default parsing options
+    ///
+    /// # Errors
+    /// This function will return an [Error] if buffer cannot be parsed
+    pub fn from_string(buf: &str) -> Result<Document, Error> {
+        Document::parse_with(buf, &ParserConfig::default())
+    }
+
+    /// Construct document from file with default parsing options
+    ///
+    /// # Errors
+    /// This function will return an [Error] if file cannot be opened or parsed
+    pub fn from_file<S>(path: &S) -> Result<Document, Error>
+    where
+        S: AsRef<Path> + ?Sized,
+    {
+        let mut buffer = String::new();
+        BufReader::new(File::open(path)?).read_to_string(&mut buffer)?;
+        Document::from_string(&buffer)
+    }
+
+    /// Write document to file, replacing it atomically like [Ini::to_file](crate::Ini::to_file)
+    ///
+    /// # Errors
+    /// Errors returned by [File::create], [Write::write_all](std::io::Write::write_all) and [fs::rename]
+    pub fn to_file<S>(&self, path: &S) -> Result<(), io::Error>
+    where
+        S: AsRef<Path> + ?Sized,
+    {
+        atomic::write(path.as_ref(), false, |writer| writer.write_all(self.to_string().as_bytes()))
+    }
+
+    /// Change file in place with `edit` function
+    ///
+    /// Only lines of modified keys are changed, the rest of file stays byte-identical.
+    /// File is not rewritten if `edit` made no changes.
+    ///
+    /// # Errors
+    /// This function will return an [Error] if file cannot be read, parsed or written
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use tini::Document;
+    /// Document::edit_file("app.ini", |doc| {
+    ///     doc.set("server", "port", 8080)?;
+    ///     doc.remove("server", "legacy");
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn edit_file<S, F>(path: &S, edit: F) -> Result<(), Error>
+    where
+        S: AsRef<Path> + ?Sized,
+        F: FnOnce(&mut Document) -> Result<(), Error>,
+    {
+        let original = fs::read_to_string(path)?;
+        let mut document = Document::from_string(&original)?;
+        edit(&mut document)?;
+        let edited = document.to_string();
+        if edited != original {
+            atomic::write(path.as_ref(), false, |writer| writer.write_all(edited.as_bytes()))?;
+        }
+        Ok(())
+    }
+
+    /// Construct document from string with given parsing options
+    ///
+    /// # Errors
+    /// This function will return an [Error] if buffer cannot be parsed
+    pub fn parse_with(buf: &str, config: &ParserConfig) -> Result<Document, Error> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (index, raw) in buf.split_inclusive('\n').enumerate() {
+            let line = raw.trim_end_matches(&['\r', '\n'][..]);
+            let line = if index == 0 { line.trim_start_matches('\u{feff}') } else { line };
+            let offset = raw.len() - raw.trim_start_matches('\u{feff}').len();
+            let parsed = parse_line(line, index + 1, config).map_err(|e| e.shifted(start + offset))?;
+            start += raw.len();
+            let kind = match parsed {
+                Parsed::Empty => Kind::Other,
+                Parsed::Section(name, _) => Kind::Section(normalize(config, &name)),
+                Parsed::Value(key, value, _) => {
+                    let span = value_span(line, config);
+                    Kind::Value(normalize(config, &key), value, span.start + offset..span.end + offset)
+                }
+            };
+            lines.push(Line { raw: raw.to_owned(), kind });
+        }
+        Ok(Document { lines, config: config.clone(), line_ending: LineEnding::detect(buf) })
+    }
+
+    /// Iterate over lines with names of sections they belong to
+    fn sections(&self) -> impl Iterator<Item = (&str, &Line)> {
+        let mut section = self.config.global_section.as_str();
+        self.lines.iter().map(move |line| {
+            if let Kind::Section(name) = &line.kind {
+                section = name;
+            }
+            (section, line)
+        })
+    }
+
+    /// Parse `raw` text of a single line, `None` if it's invalid or contains line breaks inside
+    fn parse_single(&self, raw: &str) -> Option<Line> {
+        Document::parse_with(raw, &self.config).ok().filter(|d| d.lines.len() == 1).and_then(|mut d| d.lines.pop())
+    }
+
+    /// Find index of the last line with `key` in `section`
+    fn find(&self, section: &str, key: &str) -> Option<usize> {
+        let section = normalize(&self.config, section);
+        let key = normalize(&self.config, key);
+        self.sections()
+            .enumerate()
+            .filter(|(_, (name, line))| *name == section && matches!(&line.kind, Kind::Value(k, _, _) if *k == key))
+            .map(|(index, _)| index)
+            .last()
+    }
+
+    /// Get raw value of `key` in `section`
+    pub fn get_raw(&self, section: &str, key: &str) -> Option<&str> {
+        match &self.lines[self.find(section, key)?].kind {
+            Kind::Value(_, value, _) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get scalar value of `key` in `section`
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    pub fn get<T>(&self, section: &str, key: &str) -> Option<T>
+    where
+        T: FromIniValue,
+    {
+        self.get_raw(section, key).and_then(|value| T::parse_value(value))
+    }
+
+    /// Set value of `key` in `section`
+    ///
+    /// Value of existing key is replaced in place, keeping the rest of line untouched. Quoted value is written
+    /// in quotes again. New key is added after the last key of section, and new section is added to the end
+    /// of document. If [quoted values](ParserConfig::quoted_values) are enabled, values with line breaks,
+    /// comment characters or surrounding spaces are quoted.
+    ///
+    /// # Errors
+    /// [ValidationError] if section, key or value would not be parsed back, document is left unchanged then
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Document, Error, ValidationError};
+    /// let mut doc = Document::from_string("\u{feff}[a]\nx = 1\n").unwrap();
+    ///
+    /// doc.set("", "top", 1).unwrap();
+    /// assert_eq!(doc.to_string(), "\u{feff}top = 1\n[a]\nx = 1\n");
+    ///
+    /// let error = doc.set("a", "x", "two\nlines").unwrap_err();
+    /// assert!(matches!(error, Error::Validation(ValidationError::InvalidValue(..))));
+    /// assert_eq!(doc.get_raw("a", "x"), Some("1"));
+    /// ```
+    pub fn set<V>(&mut self, section: &str, key: &str, value: V) -> Result<(), Error>
+    where
+        V: ToIniValue,
+    {
+        let value = value.format_value();
+        let invalid_value = || ValidationError::InvalidValue(key.to_owned(), value.clone());
+        if let Some(index) = self.find(section, key) {
+            let line = &self.lines[index];
+            if let Kind::Value(name, _, span) = &line.kind {
+                let quoted = self.config.quoted_values
+                    && (line.raw[span.clone()].starts_with(&['"', '\''][..]) || needs_quotes(&value, '='));
+                let mut raw = line.raw.clone();
+                raw.replace_range(span.clone(), &if quoted { quote(&value) } else { value.clone() });
+                let parsed = self
+                    .parse_single(&raw)
+                    .filter(|line| matches!(&line.kind, Kind::Value(k, v, _) if k == name && *v == value))
+                    .ok_or_else(invalid_value)?;
+                self.lines[index] = parsed;
+            }
+            return Ok(());
+        }
+        let name = normalize(&self.config, section);
+        let key_name = normalize(&self.config, key);
+        let position = self
+            .sections()
+            .enumerate()
+            .filter(|(_, (s, line))| *s == name && !matches!(line.kind, Kind::Other))
+            .map(|(index, _)| index + 1)
+            .last();
+        let mut new_lines = Vec::new();
+        if position.is_none() && name != self.config.global_section {
+            new_lines.push(format!("[{}]", section));
+        }
+        let quoted = self.config.quoted_values && needs_quotes(&value, '=');
+        new_lines.push(format!("{} = {}", key, if quoted { quote(&value) } else { value.clone() }));
+        let position = position.unwrap_or(if new_lines.len() == 1 { 0 } else { self.lines.len() });
+        // byte order mark stays at the start of document
+        let bom = position == 0 && self.lines.first().is_some_and(|line| line.raw.starts_with('\u{feff}'));
+        let mut parsed = Vec::new();
+        for (offset, raw) in new_lines.iter().enumerate() {
+            let prefix = if bom && offset == 0 { "\u{feff}" } else { "" };
+            let line = self.parse_single(&format!("{}{}{}", prefix, raw, self.line_ending.as_str()));
+            match line.as_ref().map(|line| &line.kind) {
+                Some(Kind::Section(s)) if *s == name => (),
+                Some(Kind::Value(k, v, _)) if *k == key_name && *v == value => (),
+                Some(Kind::Value(k, _, _)) if *k == key_name => return Err(invalid_value().into()),
+                _ if offset + 1 < new_lines.len() => {
+                    return Err(ValidationError::InvalidSection(section.to_owned()).into())
+                }
+                _ if key.contains(&['\n', '\r'][..]) || !value.contains(&['\n', '\r'][..]) => {
+                    return Err(ValidationError::InvalidKey(key.to_owned()).into())
+                }
+                _ => return Err(invalid_value().into()),
+            }
+            parsed.extend(line);
+        }
+        if bom {
+            let first = self.lines[0].raw.trim_start_matches('\u{feff}').to_owned();
+            if let Some(line) = self.parse_single(&first) {
+                self.lines[0] = line;
+            }
+        }
+        // previous line should be terminated before new lines are added after it
+        if position > 0 && !self.lines[position - 1].raw.ends_with('\n') {
+            self.lines[position - 1].raw.push_str(self.line_ending.as_str());
+        }
+        for (offset, line) in parsed.into_iter().enumerate() {
+            self.lines.insert(position + offset, line);
+        }
+        Ok(())
+    }
+
+    /// Remove `key` from `section`, returns `true` if key was present
+    ///
+    /// The whole line is removed, including its comment.
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        match self.find(section, key) {
+            Some(index) => {
+                self.lines.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            f.write_str(&line.raw)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::from_string(s)
+    }
+}
+
+/// convert name to lower case if parser is case-insensitive
+fn normalize(config: &ParserConfig, name: &str) -> String {
+    if config.case_insensitive {
+        name.to_lowercase()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// position of raw value in line with key-value pair
+fn value_span(line: &str, config: &ParserConfig) -> Range<usize> {
+    let content = split_comment(line, config).0;
+    let start = content.find(|c| config.delimiters.contains(&c)).map_or(content.len(), |i| i + 1);
+    let value = content[start..].trim();
+    let start = start + content[start..].len() - content[start..].trim_start().len();
+    start..start + value.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lossless() -> Result<(), Error> {
+        let input = "\u{feff}top = 1\n  ; comment\n[a]  \n  b=  2  # two\nweird line?\n\n[a]\nc = 3";
+        let config = ParserConfig::default().allow_bare_keys(true);
+        let doc = Document::parse_with(input, &config)?;
+        assert_eq!(doc.to_string(), input);
+        assert_eq!(doc.get::<u8>("", "top"), Some(1));
+        assert_eq!(doc.get::<u8>("a", "b"), Some(2));
+        assert_eq!(doc.get_raw("a", "weird line?"), Some(""));
+        Ok(())
+    }
+
+    #[test]
+    fn modify() -> Result<(), Error> {
+        let mut doc = Document::from_string("top = 1\n[a]\nb=  2  # two\n\n[c]\nd = 4")?;
+        doc.set("a", "b", "22")?;
+        doc.set("a", "x", 5)?;
+        doc.set("", "top", 0)?;
+        doc.set("", "new", 1)?;
+        doc.set("e", "f", 6)?;
+        assert!(doc.remove("c", "d"));
+        assert!(!doc.remove("c", "d"));
+        assert_eq!(doc.to_string(), "top = 0\nnew = 1\n[a]\nb=  22  # two\nx = 5\n\n[c]\n[e]\nf = 6\n");
+        Ok(())
+    }
+
+    #[test]
+    fn edit_file() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!("tini-edit-{}.ini", std::process::id()));
+        fs::write(&path, "; keep me\n[a]\nb = 1   ; one\n\tc=2\n")?;
+        Document::edit_file(&path, |doc| doc.set("a", "b", 10))?;
+        let result = fs::read_to_string(&path);
+        fs::remove_file(&path)?;
+        assert_eq!(result?, "; keep me\n[a]\nb = 10   ; one\n\tc=2\n");
+        Ok(())
+    }
+
+    #[test]
+    fn quoted() -> Result<(), Error> {
+        let config = ParserConfig::default().quoted_values(true);
+        let mut doc = Document::parse_with("[a]\nb = \"x ; y\" ; comment\n", &config)?;
+        assert_eq!(doc.get_raw("a", "b"), Some("x ; y"));
+        doc.set("a", "b", "say \"hi\"")?;
+        assert_eq!(doc.to_string(), "[a]\nb = \"say \\\"hi\\\"\" ; comment\n");
+        assert_eq!(doc.get_raw("a", "b"), Some("say \"hi\""));
+        Ok(())
+    }
+
+    #[test]
+    fn bom() -> Result<(), Error> {
+        let mut doc = Document::from_string("\u{feff}[a]\nx = 1\n")?;
+        doc.set("", "top", 1)?;
+        doc.set("", "next", 2)?;
+        doc.set("a", "x", 3)?;
+        assert_eq!(doc.to_string(), "\u{feff}top = 1\nnext = 2\n[a]\nx = 3\n");
+        let reparsed = Document::from_string(&doc.to_string())?;
+        assert_eq!(reparsed.get::<u8>("", "top"), Some(1));
+        let mut doc = Document::from_string("\u{feff}top = 1")?;
+        doc.set("", "top", 2)?;
+        assert_eq!(doc.to_string(), "\u{feff}top = 2");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() -> Result<(), Error> {
+        let input = "[a]\nx = 1\n";
+        let mut doc = Document::from_string(input)?;
+        let invalid = [
+            ("a", "x", "two\nlines"),
+            ("a", "y", "two\r\nlines"),
+            ("a", "x", "1 ; comment"),
+            ("a", "y = z", "1"),
+            ("a", "", "1"),
+            ("a", "two\nlines", "1"),
+            ("b]\n[c", "y", "1"),
+        ];
+        for (section, key, value) in invalid {
+            assert!(matches!(doc.set(section, key, value), Err(Error::Validation(_))), "{:?}", (section, key, value));
+        }
+        assert_eq!(doc.to_string(), input);
+
+        let config = ParserConfig::default().quoted_values(true);
+        let mut doc = Document::parse_with(input, &config)?;
+        doc.set("a", "x", "two\nlines")?;
+        doc.set("a", "y", " padded ; value")?;
+        assert_eq!(doc.to_string(), "[a]\nx = \"two\\nlines\"\ny = \" padded ; value\"\n");
+        assert_eq!(Document::parse_with(&doc.to_string(), &config)?.get_raw("a", "x"), Some("two\nlines"));
+        Ok(())
+    }
+}
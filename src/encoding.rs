@@ -0,0 +1,176 @@
+//! Encoding module
+//!
+//! Contains routines to decode and encode ini-files in UTF-16 and legacy single-byte code pages
+use std::io;
+
+/// Text encoding of ini-file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8
+    Utf8,
+    /// UTF-16 little endian
+    Utf16Le,
+    /// UTF-16 big endian
+    Utf16Be,
+    /// ISO-8859-1
+    Latin1,
+    /// Windows-1251 (Cyrillic)
+    Windows1251,
+    /// Windows-1252 (Western European)
+    Windows1252,
+}
+
+/// Upper half of Windows-1251 code page
+const WINDOWS_1251: [char; 128] = [
+    '\u{0402}', '\u{0403}', '\u{201a}', '\u{0453}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{20ac}',
+    '\u{2030}', '\u{0409}', '\u{2039}', '\u{040a}', '\u{040c}', '\u{040b}', '\u{040f}', '\u{0452}', '\u{2018}',
+    '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{0098}', '\u{2122}', '\u{0459}',
+    '\u{203a}', '\u{045a}', '\u{045c}', '\u{045b}', '\u{045f}', '\u{00a0}', '\u{040e}', '\u{045e}', '\u{0408}',
+    '\u{00a4}', '\u{0490}', '\u{00a6}', '\u{00a7}', '\u{0401}', '\u{00a9}', '\u{0404}', '\u{00ab}', '\u{00ac}',
+    '\u{00ad}', '\u{00ae}', '\u{0407}', '\u{00b0}', '\u{00b1}', '\u{0406}', '\u{0456}', '\u{0491}', '\u{00b5}',
+    '\u{00b6}', '\u{00b7}', '\u{0451}', '\u{2116}', '\u{0454}', '\u{00bb}', '\u{0458}', '\u{0405}', '\u{0455}',
+    '\u{0457}', '\u{0410}', '\u{0411}', '\u{0412}', '\u{0413}', '\u{0414}', '\u{0415}', '\u{0416}', '\u{0417}',
+    '\u{0418}', '\u{0419}', '\u{041a}', '\u{041b}', '\u{041c}', '\u{041d}', '\u{041e}', '\u{041f}', '\u{0420}',
+    '\u{0421}', '\u{0422}', '\u{0423}', '\u{0424}', '\u{0425}', '\u{0426}', '\u{0427}', '\u{0428}', '\u{0429}',
+    '\u{042a}', '\u{042b}', '\u{042c}', '\u{042d}', '\u{042e}', '\u{042f}', '\u{0430}', '\u{0431}', '\u{0432}',
+    '\u{0433}', '\u{0434}', '\u{0435}', '\u{0436}', '\u{0437}', '\u{0438}', '\u{0439}', '\u{043a}', '\u{043b}',
+    '\u{043c}', '\u{043d}', '\u{043e}', '\u{043f}', '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0444}',
+    '\u{0445}', '\u{0446}', '\u{0447}', '\u{0448}', '\u{0449}', '\u{044a}', '\u{044b}', '\u{044c}', '\u{044d}',
+    '\u{044e}', '\u{044f}',
+];
+
+/// Upper half of Windows-1252 code page
+const WINDOWS_1252: [char; 128] = [
+    '\u{20ac}', '\u{0081}', '\u{201a}', '\u{0192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{02c6}',
+    '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008d}', '\u{017d}', '\u{008f}', '\u{0090}', '\u{2018}',
+    '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{02dc}', '\u{2122}', '\u{0161}',
+    '\u{203a}', '\u{0153}', '\u{009d}', '\u{017e}', '\u{0178}', '\u{00a0}', '\u{00a1}', '\u{00a2}', '\u{00a3}',
+    '\u{00a4}', '\u{00a5}', '\u{00a6}', '\u{00a7}', '\u{00a8}', '\u{00a9}', '\u{00aa}', '\u{00ab}', '\u{00ac}',
+    '\u{00ad}', '\u{00ae}', '\u{00af}', '\u{00b0}', '\u{00b1}', '\u{00b2}', '\u{00b3}', '\u{00b4}', '\u{00b5}',
+    '\u{00b6}', '\u{00b7}', '\u{00b8}', '\u{00b9}', '\u{00ba}', '\u{00bb}', '\u{00bc}', '\u{00bd}', '\u{00be}',
+    '\u{00bf}', '\u{00c0}', '\u{00c1}', '\u{00c2}', '\u{00c3}', '\u{00c4}', '\u{00c5}', '\u{00c6}', '\u{00c7}',
+    '\u{00c8}', '\u{00c9}', '\u{00ca}', '\u{00cb}', '\u{00cc}', '\u{00cd}', '\u{00ce}', '\u{00cf}', '\u{00d0}',
+    '\u{00d1}', '\u{00d2}', '\u{00d3}', '\u{00d4}', '\u{00d5}', '\u{00d6}', '\u{00d7}', '\u{00d8}', '\u{00d9}',
+    '\u{00da}', '\u{00db}', '\u{00dc}', '\u{00dd}', '\u{00de}', '\u{00df}', '\u{00e0}', '\u{00e1}', '\u{00e2}',
+    '\u{00e3}', '\u{00e4}', '\u{00e5}', '\u{00e6}', '\u{00e7}', '\u{00e8}', '\u{00e9}', '\u{00ea}', '\u{00eb}',
+    '\u{00ec}', '\u{00ed}', '\u{00ee}', '\u{00ef}', '\u{00f0}', '\u{00f1}', '\u{00f2}', '\u{00f3}', '\u{00f4}',
+    '\u{00f5}', '\u{00f6}', '\u{00f7}', '\u{00f8}', '\u{00f9}', '\u{00fa}', '\u{00fb}', '\u{00fc}', '\u{00fd}',
+    '\u{00fe}', '\u{00ff}',
+];
+
+impl Encoding {
+    /// Detect encoding by byte order mark, UTF-8 is assumed if there is no one
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Encoding;
+    /// assert_eq!(Encoding::detect(b"\xff\xfe[\x00"), Encoding::Utf16Le);
+    /// assert_eq!(Encoding::detect(b"[a]"), Encoding::Utf8);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        match bytes {
+            [0xff, 0xfe, ..] => Encoding::Utf16Le,
+            [0xfe, 0xff, ..] => Encoding::Utf16Be,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// table of upper half of single byte encoding
+    fn table(&self) -> Option<&'static [char; 128]> {
+        match self {
+            Encoding::Windows1251 => Some(&WINDOWS_1251),
+            Encoding::Windows1252 => Some(&WINDOWS_1252),
+            _ => None,
+        }
+    }
+
+    /// decode `bytes` to string, byte order mark is kept as `\u{feff}`
+    pub(crate) fn decode(&self, bytes: &[u8]) -> Result<String, io::Error> {
+        match self {
+            Encoding::Utf8 => String::from_utf8(bytes.to_vec()).map_err(|e| invalid_data(e.to_string())),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                if !bytes.len().is_multiple_of(2) {
+                    return Err(invalid_data("odd number of bytes in UTF-16 data"));
+                }
+                let units = bytes.chunks(2).map(|pair| match self {
+                    Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                });
+                std::char::decode_utf16(units).collect::<Result<_, _>>().map_err(|e| invalid_data(e.to_string()))
+            }
+            Encoding::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+            Encoding::Windows1251 | Encoding::Windows1252 => {
+                let table = self.table().unwrap_or(&WINDOWS_1252);
+                Ok(bytes.iter().map(|&b| if b < 0x80 { b as char } else { table[b as usize - 0x80] }).collect())
+            }
+        }
+    }
+
+    /// encode string to bytes
+    pub(crate) fn encode(&self, string: &str) -> Result<Vec<u8>, io::Error> {
+        match self {
+            Encoding::Utf8 => Ok(string.as_bytes().to_vec()),
+            Encoding::Utf16Le => Ok(string.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Encoding::Utf16Be => Ok(string.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Encoding::Latin1 => {
+                string.chars().map(|c| if (c as u32) < 0x100 { Ok(c as u8) } else { Err(unmappable(c)) }).collect()
+            }
+            Encoding::Windows1251 | Encoding::Windows1252 => {
+                let table = self.table().unwrap_or(&WINDOWS_1252);
+                string
+                    .chars()
+                    .map(|c| match table.iter().position(|&t| t == c) {
+                        _ if c.is_ascii() => Ok(c as u8),
+                        Some(index) => Ok(index as u8 + 0x80),
+                        None => Err(unmappable(c)),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn unmappable(c: char) -> io::Error {
+    invalid_data(format!("character {:?} can not be encoded", c))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() -> Result<(), io::Error> {
+        let cases = [
+            (Encoding::Utf16Le, "[секция]\nключ = 𝄞"),
+            (Encoding::Utf16Be, "[секция]\nключ = 𝄞"),
+            (Encoding::Latin1, "[a]\nb = café"),
+            (Encoding::Windows1251, "[секция]\nключ = «значение» №1"),
+            (Encoding::Windows1252, "[a]\nb = 5 € – œ"),
+        ];
+        for (encoding, text) in &cases {
+            assert_eq!(encoding.decode(&encoding.encode(text)?)?, *text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn windows1251() -> Result<(), io::Error> {
+        assert_eq!(Encoding::Windows1251.decode(b"\xcf\xf0\xe8\xe2\xe5\xf2 = 1")?, "Привет = 1");
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Encoding::Latin1.encode("€").is_err());
+        assert!(Encoding::Windows1251.encode("é").is_err());
+        assert!(Encoding::Utf16Le.decode(b"[\x00a").is_err());
+        assert!(Encoding::Utf16Le.decode(b"\x00\xd8").is_err());
+    }
+}
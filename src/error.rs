@@ -10,42 +10,392 @@ pub enum Error {
     Io(io::Error),
     /// Parsing errors
     Parse(ParseError),
+    /// Value interpolation errors
+    Interpolation(InterpolationError),
+    /// Invalid names or values on construction
+    Validation(ValidationError),
+    /// Conflicts on merging documents
+    Merge(MergeError),
+    /// Errors of building structures from documents
+    Mapping(MappingError),
+    /// Malformed `section.key=value` overrides
+    Override(OverrideError),
 }
 
-/// Enum for storing one of the possible errors code.
-/// The associated value represents the row index where the error occurred.
-#[derive(Debug)]
-pub enum ParseError {
+/// Enum for storing one of the possible parsing errors code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
     /// Section has incorrect syntax
-    IncorrectSection(usize),
+    IncorrectSection,
     /// Unknown syntax format
-    IncorrectSyntax(usize),
+    IncorrectSyntax,
     /// Key has empty name
-    EmptyKey(usize),
+    EmptyKey,
+    /// Key is already defined in section
+    DuplicateKey,
+    /// Section is already defined in document
+    DuplicateSection,
+    /// Key is placed before the first section header
+    KeyOutsideSection,
+    /// Input is larger than [`max_input_size()`](crate::ParserConfig::max_input_size)
+    InputTooLarge,
+    /// Line is longer than [`max_line_length()`](crate::ParserConfig::max_line_length)
+    LineTooLong,
+    /// Document has more sections than [`max_sections()`](crate::ParserConfig::max_sections)
+    TooManySections,
+    /// Document has more keys than [`max_keys()`](crate::ParserConfig::max_keys)
+    TooManyKeys,
+    /// Section or key is rejected by [`on_section()`](crate::ParserConfig::on_section) or
+    /// [`on_entry()`](crate::ParserConfig::on_entry) hook
+    Rejected,
+}
+
+/// Parsing error with position of the problem in input
+///
+/// [Display](#impl-Display-for-ParseError) shows the offending line with a caret pointing at the bad character.
+///
+/// # Example
+/// ```
+/// # use tini::{Error, Ini, ParseErrorKind};
+/// match Ini::from_string("[a]\nkey = 1\n  [broken") {
+///     Err(Error::Parse(error)) => {
+///         assert_eq!(error.kind(), ParseErrorKind::IncorrectSection);
+///         assert_eq!((error.line(), error.column(), error.offset()), (3, 10, 21));
+///         assert_eq!(error.text(), "  [broken");
+///         assert_eq!(error.to_string(), "Incorrect section syntax at line 3, column 10\n  [broken\n         ^");
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    line: usize,
+    column: usize,
+    offset: usize,
+    text: String,
+}
+
+impl ParseError {
+    /// Create error for `position` in bytes in line `text` with number `line`
+    pub(crate) fn new(kind: ParseErrorKind, line: usize, text: &str, position: usize) -> ParseError {
+        let column = text[..position].chars().count() + 1;
+        ParseError { kind, line, column, offset: position, text: text.to_owned() }
+    }
+
+    /// Move error position by `offset` of line start in input
+    pub(crate) fn shifted(mut self, offset: usize) -> ParseError {
+        self.offset += offset;
+        self
+    }
+
+    /// Kind of error
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Line number where the error occurred, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column of the bad character in characters, starting from 1
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Offset of the bad character in bytes from the start of input
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Content of the offending line
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Problem found on a single line by [Ini::parse_lossy](crate::Ini::parse_lossy)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIssue {
+    /// line number, starting from 1
+    pub line: usize,
+    /// content of malformed line
+    pub text: String,
+    /// kind of problem
+    pub error: ParseError,
+}
+
+/// Reason why [Ini::try_get](crate::Ini::try_get) cannot return a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GetError {
+    /// Section is absent
+    MissingSection,
+    /// Section is present, but key is absent
+    MissingKey,
+    /// Key is present but its value is empty
+    Empty,
+    /// Value cannot be converted to requested type
+    ParseFailed {
+        /// raw value
+        value: String,
+        /// name of requested type
+        type_name: &'static str,
+    },
+    /// Element of vector value cannot be converted to requested type
+    InvalidElement {
+        /// index of element, starting from 0
+        index: usize,
+        /// raw element without surrounding whitespace
+        value: String,
+        /// name of requested element type
+        type_name: &'static str,
+    },
+}
+
+/// Enum for storing one of the possible errors of [try_section](crate::Ini::try_section),
+/// [try_item](crate::Ini::try_item) and [Document::set](crate::Document::set)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Section name would not be parsed back, the associated value is the name
+    InvalidSection(String),
+    /// Key name would not be parsed back, the associated value is the name
+    InvalidKey(String),
+    /// Value would not be parsed back, the associated values are key and value
+    InvalidValue(String, String),
+}
+
+/// Enum for storing one of the possible interpolation errors.
+/// The associated values represent section and key where the error occurred.
+#[derive(Debug)]
+pub enum InterpolationError {
+    /// Reference to absent key
+    MissingReference(String, String),
+    /// Key references itself directly or through other keys
+    Cycle(String, String),
+    /// Reference is not closed with `}` or empty
+    IncorrectSyntax(String, String),
+    /// Environment variable is not set, the last value is the name of variable
+    UnsetVariable(String, String, String),
+}
+
+/// Enum for storing one of the possible errors of [Ini::merge](crate::Ini::merge).
+/// The associated values represent section and key where the error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// Key is present in both documents with different values
+    Conflict(String, String),
+}
+
+/// Enum for storing one of the possible errors of [apply_overrides](crate::Ini::apply_overrides).
+/// The associated values represent position of override in input and override itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideError {
+    /// Override has no `=` between path and value
+    MissingValue(usize, String),
+    /// Path has no `.` between section and key or key is empty
+    InvalidPath(usize, String),
+}
+
+/// Error of [FromIni](crate::FromIni) or [FromSection](crate::FromSection) conversion
+///
+/// # Example
+/// ```
+/// # use tini::{GetError, MappingError};
+/// let error = MappingError::key("server", "port", GetError::MissingKey);
+/// assert_eq!(error.to_string(), "Key is not set: key `port` in section `server`");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingError {
+    /// section name, empty if error is returned by [FromSection](crate::FromSection) alone
+    pub section: String,
+    /// key name, `None` if error relates to the whole section
+    pub key: Option<String>,
+    /// reason of error
+    pub error: GetError,
+}
+
+impl MappingError {
+    /// Create error of `key` in `section`
+    pub fn key(section: &str, key: &str, error: GetError) -> MappingError {
+        MappingError { section: section.to_owned(), key: Some(key.to_owned()), error }
+    }
+
+    /// Create error of the whole `section`
+    pub fn section(section: &str, error: GetError) -> MappingError {
+        MappingError { section: section.to_owned(), key: None, error }
+    }
+
+    /// Set section name of error returned by [FromSection](crate::FromSection)
+    pub fn in_section(mut self, section: &str) -> MappingError {
+        self.section = section.to_owned();
+        self
+    }
 }
 
 impl error::Error for Error {}
 impl error::Error for ParseError {}
+impl error::Error for InterpolationError {}
+impl error::Error for GetError {}
+impl error::Error for ValidationError {}
+impl error::Error for MergeError {}
+impl error::Error for MappingError {}
+impl error::Error for OverrideError {}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::Io(ref e) => e.fmt(f),
             Error::Parse(ref e) => e.fmt(f),
+            Error::Interpolation(ref e) => e.fmt(f),
+            Error::Validation(ref e) => e.fmt(f),
+            Error::Merge(ref e) => e.fmt(f),
+            Error::Mapping(ref e) => e.fmt(f),
+            Error::Override(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::IncorrectSection => write!(f, "Incorrect section syntax"),
+            ParseErrorKind::IncorrectSyntax => write!(f, "Incorrect syntax"),
+            ParseErrorKind::EmptyKey => write!(f, "Key is empty"),
+            ParseErrorKind::DuplicateKey => write!(f, "Duplicate key"),
+            ParseErrorKind::DuplicateSection => write!(f, "Duplicate section"),
+            ParseErrorKind::KeyOutsideSection => write!(f, "Key outside of section"),
+            ParseErrorKind::InputTooLarge => write!(f, "Input size limit exceeded"),
+            ParseErrorKind::LineTooLong => write!(f, "Line length limit exceeded"),
+            ParseErrorKind::TooManySections => write!(f, "Section count limit exceeded"),
+            ParseErrorKind::TooManyKeys => write!(f, "Key count limit exceeded"),
+            ParseErrorKind::Rejected => write!(f, "Rejected by parser hook"),
         }
     }
 }
 
 impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // keep tabs in caret line to align it with the offending line
+        let padding: String =
+            self.text.chars().take(self.column - 1).map(|c| if c == '\t' { c } else { ' ' }).collect();
+        write!(f, "{} at line {}, column {}\n{}\n{}^", self.kind, self.line, self.column, self.text, padding)
+    }
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl fmt::Display for GetError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParseError::IncorrectSection(line) => write!(f, "Incorrect section syntax at line {}", line),
-            ParseError::IncorrectSyntax(line) => write!(f, "Incorrect syntax at line {}", line),
-            ParseError::EmptyKey(line) => write!(f, "Key is empty at line {}", line),
+            GetError::MissingSection => write!(f, "Section is not set"),
+            GetError::MissingKey => write!(f, "Key is not set"),
+            GetError::Empty => write!(f, "Key has empty value"),
+            GetError::ParseFailed { value, type_name } => {
+                write!(f, "Value `{}` cannot be parsed as `{}`", value, type_name)
+            }
+            GetError::InvalidElement { index, value, type_name } => {
+                write!(f, "Element {} `{}` cannot be parsed as `{}`", index, value, type_name)
+            }
         }
     }
 }
 
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::InvalidSection(name) => write!(f, "Invalid section name `{}`", name),
+            ValidationError::InvalidKey(name) => write!(f, "Invalid key name `{}`", name),
+            ValidationError::InvalidValue(key, value) => write!(f, "Invalid value `{}` of key `{}`", value, key),
+        }
+    }
+}
+
+impl fmt::Display for InterpolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpolationError::MissingReference(section, key) => {
+                write!(f, "Reference to missing key `{}` in section `{}`", key, section)
+            }
+            InterpolationError::Cycle(section, key) => {
+                write!(f, "Cyclic reference to key `{}` in section `{}`", key, section)
+            }
+            InterpolationError::IncorrectSyntax(section, key) => {
+                write!(f, "Incorrect reference syntax in key `{}` in section `{}`", key, section)
+            }
+            InterpolationError::UnsetVariable(section, key, variable) => {
+                write!(f, "Environment variable `{}` is not set for key `{}` in section `{}`", variable, key, section)
+            }
+        }
+    }
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::Conflict(section, key) => {
+                write!(f, "Conflicting values of key `{}` in section `{}`", key, section)
+            }
+        }
+    }
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideError::MissingValue(index, text) => {
+                write!(f, "Override #{} `{}` has no `=` between path and value", index + 1, text)
+            }
+            OverrideError::InvalidPath(index, text) => {
+                write!(f, "Override #{} `{}` has no `section.key` path", index + 1, text)
+            }
+        }
+    }
+}
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "{}: key `{}` in section `{}`", self.error, key, self.section),
+            None => write!(f, "{}: section `{}`", self.error, self.section),
+        }
+    }
+}
+
+impl From<OverrideError> for Error {
+    fn from(error: OverrideError) -> Self {
+        Error::Override(error)
+    }
+}
+
+impl From<MappingError> for Error {
+    fn from(error: MappingError) -> Self {
+        Error::Mapping(error)
+    }
+}
+
+impl From<MergeError> for Error {
+    fn from(error: MergeError) -> Self {
+        Error::Merge(error)
+    }
+}
+
+impl From<InterpolationError> for Error {
+    fn from(error: InterpolationError) -> Self {
+        Error::Interpolation(error)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        Error::Validation(error)
+    }
+}
+
 impl From<ParseError> for Error {
     fn from(error: ParseError) -> Self {
         Error::Parse(error)
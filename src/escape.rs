@@ -0,0 +1,218 @@
+//! Escape module
+//!
+//! Contains routines to quote values with escape sequences and to decode them back
+//! and `QuoteMode` enum to control quoting on output
+use std::borrow::Cow;
+use std::str::Chars;
+
+/// Quoting of values on output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteMode {
+    /// values are written as is
+    #[default]
+    Never,
+    /// every value is wrapped in double quotes and special characters are escaped
+    Always,
+    /// only values which would not be parsed back as is are quoted: values with leading or trailing
+    /// whitespace, comment characters, delimiter, line breaks or starting with quote
+    Auto,
+}
+
+/// find index of closing quote in string started with quote
+pub fn closing_quote(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices();
+    let (_, quote) = chars.next()?;
+    let mut escaped = false;
+    for (index, c) in chars {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// strip quotes and decode escape sequences if value is wrapped in single or double quotes
+pub fn unquote(value: &str) -> Option<String> {
+    if !value.starts_with(&['"', '\''][..]) || closing_quote(value)? != value.len() - 1 {
+        return None;
+    }
+    Some(unescape(&value[1..value.len() - 1]))
+}
+
+/// decode escape sequences, unknown sequences are kept as is
+pub fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('0') => result.push('\0'),
+            Some(c @ '\\') | Some(c @ '"') | Some(c @ '\'') => result.push(c),
+            Some('u') => match decode_unicode(&mut chars) {
+                Some(decoded) => result.push(decoded),
+                None => result.push_str("\\u"),
+            },
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// decode `XXXX` or UTF-16 surrogate pair `XXXX\\uXXXX` following `\\u`, `chars` are consumed only on success
+pub fn decode_unicode(chars: &mut Chars<'_>) -> Option<char> {
+    fn hex(chars: &mut Chars<'_>) -> Option<u32> {
+        let code = chars.as_str().get(..4).filter(|code| code.chars().all(|c| c.is_ascii_hexdigit()))?;
+        let value = u32::from_str_radix(code, 16).ok()?;
+        chars.nth(3);
+        Some(value)
+    }
+    let mut rest = chars.clone();
+    let code = match hex(&mut rest)? {
+        high @ 0xD800..=0xDBFF => {
+            rest = rest.as_str().strip_prefix("\\u")?.chars();
+            match hex(&mut rest)? {
+                low @ 0xDC00..=0xDFFF => 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                _ => return None,
+            }
+        }
+        code => code,
+    };
+    let decoded = char::from_u32(code)?;
+    *chars = rest;
+    Some(decoded)
+}
+
+/// decode only `\\uXXXX` sequences, other characters including backslashes are kept as is
+pub fn unescape_unicode(s: &str) -> Cow<'_, str> {
+    if !s.contains("\\u") {
+        return Cow::Borrowed(s);
+    }
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.as_str().starts_with('u') {
+            let mut rest = chars.clone();
+            rest.next();
+            if let Some(decoded) = decode_unicode(&mut rest) {
+                result.push(decoded);
+                chars = rest;
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    Cow::Owned(result)
+}
+
+/// replace non-ASCII characters with `\\uXXXX` sequences, characters outside of BMP are written as surrogate pairs
+pub fn escape_non_ascii(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+    let mut result = String::with_capacity(s.len() * 2);
+    for c in s.chars() {
+        if c.is_ascii() {
+            result.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                result.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// encode special characters with escape sequences
+pub fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// wrap value in double quotes escaping special characters
+pub fn quote(s: &str) -> String {
+    format!("\"{}\"", escape(s))
+}
+
+/// check if value should be quoted to be parsed back as is
+pub fn needs_quotes(s: &str, delimiter: char) -> bool {
+    s.trim() != s
+        || s.starts_with(&['"', '\''][..])
+        || s.contains(|c: char| matches!(c, ';' | '#' | '\n' | '\r') || c == delimiter || c.is_control())
+}
+
+/// quote value according to `mode`
+pub fn quote_with(s: &str, mode: QuoteMode, delimiter: char) -> Cow<'_, str> {
+    match mode {
+        QuoteMode::Always => Cow::Owned(quote(s)),
+        QuoteMode::Auto if needs_quotes(s, delimiter) => Cow::Owned(quote(s)),
+        _ => Cow::Borrowed(s),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn auto_quoting() {
+        for value in ["plain value", "a=b", "", "it's"] {
+            assert_eq!(quote_with(value, QuoteMode::Auto, ':'), value);
+        }
+        for value in [" padded", "a:b", "a ; b", "#hash", "'quoted'", "two\nlines"] {
+            assert_eq!(quote_with(value, QuoteMode::Auto, ':'), quote(value));
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let value = "tab\there \"quoted\" back\\slash\nnew line \u{1}";
+        assert_eq!(unquote(&quote(value)), Some(value.to_owned()));
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(unescape("\\u0041\\u00e9 \\uzz \\u12"), "Aé \\uzz \\u12");
+    }
+
+    #[test]
+    fn non_ascii() {
+        let value = "caf\u{e9} \u{1f600} \\path";
+        assert_eq!(escape_non_ascii(value), "caf\\u00e9 \\ud83d\\ude00 \\path");
+        assert_eq!(unescape_unicode(&escape_non_ascii(value)), value);
+        assert_eq!(unescape_unicode("\\ud83d \\u12 \\user"), "\\ud83d \\u12 \\user");
+        assert_eq!(unescape("\\ud83d\\ude00"), "\u{1f600}");
+    }
+
+    #[test]
+    fn unquote_requires_both_quotes() {
+        assert_eq!(unquote("'single'"), Some("single".to_owned()));
+        assert_eq!(unquote("\"open"), None);
+        assert_eq!(unquote("\"a\" b\""), None);
+        assert_eq!(unquote("plain"), None);
+    }
+}
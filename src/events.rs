@@ -0,0 +1,173 @@
+//! Events module
+//!
+//! Contains [IniEvents] pull parser which yields [Event] for every meaningful line of input
+use crate::config::ParserConfig;
+use crate::error::{ParseError, ParseErrorKind};
+use crate::parser::{indentation, parse_line, split_comment, Parsed};
+use std::ops::Range;
+use std::str::SplitInclusive;
+
+/// Event produced by [IniEvents] parser
+///
+/// Every event carries `span`: byte range of source line(s) in input, without line break.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// section header `[name]`
+    SectionStart { name: String, span: Range<usize> },
+    /// `key = value` pair in current section
+    KeyValue { key: String, value: String, span: Range<usize> },
+    /// full-line or inline comment
+    Comment { text: String, span: Range<usize> },
+    /// line cannot be parsed, parser continues with the next line
+    Error(ParseError),
+}
+
+/// Streaming ini parser
+///
+/// Parser does not store parsed data, so it can be used to pick a few keys from large inputs without building
+/// a whole [Ini](crate::Ini). Duplicate policies and multi-line values are not applied in this mode:
+/// each key-value pair is reported as it appears in input.
+///
+/// # Example
+/// ```
+/// # use tini::{Event, IniEvents};
+/// let input = "; generated\n[server]\nport = 8080 ; http\n";
+/// let mut port = None;
+/// for event in IniEvents::new(input) {
+///     if let Event::KeyValue { key, value, .. } = event {
+///         if key == "port" {
+///             port = value.parse::<u16>().ok();
+///             break;
+///         }
+///     }
+/// }
+/// assert_eq!(port, Some(8080));
+///
+/// let events: Vec<Event> = IniEvents::new(input).collect();
+/// assert_eq!(events[0], Event::Comment { text: "generated".to_owned(), span: 0..11 });
+/// assert_eq!(events[1], Event::SectionStart { name: "server".to_owned(), span: 12..20 });
+/// ```
+pub struct IniEvents<'a> {
+    input: &'a str,
+    lines: SplitInclusive<'a, char>,
+    config: ParserConfig,
+    offset: usize,
+    line_no: usize,
+    has_section: bool,
+    pending: Option<Event>,
+}
+
+impl<'a> IniEvents<'a> {
+    /// Create parser over `input` with default parsing options
+    pub fn new(input: &'a str) -> IniEvents<'a> {
+        IniEvents::with_config(input, &ParserConfig::default())
+    }
+
+    /// Create parser over `input` with given parsing options
+    pub fn with_config(input: &'a str, config: &ParserConfig) -> IniEvents<'a> {
+        let start = if input.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+        IniEvents {
+            input,
+            lines: input[start..].split_inclusive('\n'),
+            config: config.clone(),
+            offset: start,
+            line_no: 0,
+            has_section: false,
+            pending: None,
+        }
+    }
+
+    /// next physical line with its number and span, joined with following lines ended by backslash if enabled
+    fn next_line(&mut self) -> Option<(usize, String, Range<usize>)> {
+        let raw = self.lines.next()?;
+        self.line_no += 1;
+        let line_no = self.line_no;
+        let start = self.offset;
+        self.offset += raw.len();
+        let mut line = raw.trim_end_matches(&['\r', '\n'][..]).to_owned();
+        while self.config.line_continuation && line.trim_end().ends_with('\\') {
+            line = line.trim_end().trim_end_matches('\\').to_owned();
+            match self.lines.next() {
+                Some(raw) => {
+                    self.line_no += 1;
+                    self.offset += raw.len();
+                    line.push_str(raw.trim_end_matches(&['\r', '\n'][..]).trim_start());
+                }
+                None => break,
+            }
+        }
+        let end = start + self.input[start..self.offset].trim_end_matches(&['\r', '\n'][..]).len();
+        Some((line_no, line, start..end))
+    }
+
+    fn normalize(&self, name: String) -> String {
+        if self.config.case_insensitive {
+            name.to_lowercase()
+        } else {
+            name
+        }
+    }
+}
+
+impl<'a> Iterator for IniEvents<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        loop {
+            let (line_no, line, span) = self.next_line()?;
+            let parsed = match parse_line(&line, line_no, &self.config) {
+                Ok(parsed) => parsed,
+                Err(error) => return Some(Event::Error(error.shifted(span.start))),
+            };
+            let comment = match &parsed {
+                // header comment is recognized even if inline comments are disabled
+                Parsed::Section(_, Some(comment)) => Some(comment.clone()),
+                _ => split_comment(&line, &self.config).1.map(|c| c.trim().to_owned()),
+            };
+            let comment = comment.map(|text| Event::Comment { text, span: span.clone() });
+            let event = match parsed {
+                Parsed::Empty => match comment {
+                    Some(comment) => return Some(comment),
+                    None => continue,
+                },
+                Parsed::Section(name, _) => {
+                    self.has_section = true;
+                    Event::SectionStart { name: self.normalize(name), span }
+                }
+                Parsed::Value(..) if self.config.strict && !self.has_section => {
+                    let error = ParseError::new(ParseErrorKind::KeyOutsideSection, line_no, &line, indentation(&line));
+                    Event::Error(error.shifted(span.start))
+                }
+                Parsed::Value(key, value, _) => Event::KeyValue { key: self.normalize(key), value, span },
+            };
+            self.pending = comment;
+            return Some(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn events() {
+        let input = "a = 1\r\n\r\n# note\r\n[Sec] ; s\r\nb = 2 \\\r\n  3\r\nbroken\r\n";
+        let config = ParserConfig::default().line_continuation(true).case_insensitive(true);
+        let events: Vec<Event> = IniEvents::with_config(input, &config).collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::KeyValue { key: "a".to_owned(), value: "1".to_owned(), span: 0..5 },
+                Event::Comment { text: "note".to_owned(), span: 9..15 },
+                Event::SectionStart { name: "sec".to_owned(), span: 17..26 },
+                Event::Comment { text: "s".to_owned(), span: 17..26 },
+                Event::KeyValue { key: "b".to_owned(), value: "2 3".to_owned(), span: 28..40 },
+                Event::Error(ParseError::new(ParseErrorKind::IncorrectSyntax, 7, "broken", 0).shifted(42)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Format module
+//!
+//! Contains options which control how ini-files are written
+use crate::escape::QuoteMode;
+
+/// Line break style on output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, used on Unix-like systems
+    #[default]
+    Lf,
+    /// `\r\n`, used on Windows
+    CrLf,
+}
+
+impl LineEnding {
+    /// Detect style of the first line break in `string`, [LineEnding::Lf] if there are no line breaks
+    pub(crate) fn detect(string: &str) -> LineEnding {
+        match string.find('\n') {
+            Some(index) if string[..index].ends_with('\r') => LineEnding::CrLf,
+            _ => LineEnding::Lf,
+        }
+    }
+
+    /// Get line break as string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Style of [Ini](crate::Ini) output
+///
+/// Options are set by method chaining and used by [`to_string_with()`](crate::Ini::to_string_with) and
+/// [`to_writer_with()`](crate::Ini::to_writer_with), or set as default style of document with
+/// [`format()`](crate::Ini::format).
+///
+/// # Example
+/// ```
+/// # use tini::{FormatOptions, Ini};
+/// let conf = Ini::new().section("a").item("b", 1).section("c").item("d", 2);
+/// let options = FormatOptions::new().spaces_around_delimiter(false).blank_lines(0).indent(2);
+///
+/// assert_eq!(conf.to_string_with(&options), "[a]\n  b=1\n[c]\n  d=2\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub(crate) delimiter: char,
+    pub(crate) spaces_around_delimiter: bool,
+    pub(crate) blank_lines: usize,
+    pub(crate) indent: usize,
+    pub(crate) align_values: bool,
+    pub(crate) trailing_newline: bool,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) wrap_width: Option<usize>,
+    pub(crate) quote_mode: QuoteMode,
+    pub(crate) escape_non_ascii: bool,
+}
+
+impl FormatOptions {
+    /// Create default options (similar to [FormatOptions::default]): `key = value` lines without indentation,
+    /// sections separated by one blank line
+    pub fn new() -> FormatOptions {
+        FormatOptions {
+            delimiter: '=',
+            spaces_around_delimiter: true,
+            blank_lines: 1,
+            indent: 0,
+            align_values: false,
+            trailing_newline: true,
+            line_ending: LineEnding::Lf,
+            wrap_width: None,
+            quote_mode: QuoteMode::Never,
+            escape_non_ascii: false,
+        }
+    }
+
+    /// Set key-value delimiter (`=` by default)
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Enable or disable spaces around delimiter (enabled by default)
+    ///
+    /// Delimiter `=` is surrounded by spaces, other delimiters are followed by space.
+    pub fn spaces_around_delimiter(mut self, enabled: bool) -> Self {
+        self.spaces_around_delimiter = enabled;
+        self
+    }
+
+    /// Set number of blank lines between sections (1 by default)
+    pub fn blank_lines(mut self, count: usize) -> Self {
+        self.blank_lines = count;
+        self
+    }
+
+    /// Set indentation of keys in spaces (0 by default)
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = width;
+        self
+    }
+
+    /// Enable or disable alignment of delimiters in section by padding keys with spaces (disabled by default)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{FormatOptions, Ini};
+    /// let conf = Ini::new().section("a").item("host", "localhost").item("port", 80).item("timeout", 5);
+    ///
+    /// assert_eq!(conf.to_string_with(&FormatOptions::new().align_values(true)),
+    ///            "[a]\nhost    = localhost\nport    = 80\ntimeout = 5\n");
+    /// ```
+    pub fn align_values(mut self, enabled: bool) -> Self {
+        self.align_values = enabled;
+        self
+    }
+
+    /// Enable or disable line break after the last line (enabled by default)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{FormatOptions, Ini};
+    /// let conf = Ini::new().section("a").item("b", 1);
+    ///
+    /// assert_eq!(conf.to_string_with(&FormatOptions::new()), "[a]\nb = 1\n");
+    /// assert_eq!(conf.to_string_with(&FormatOptions::new().trailing_newline(false)), "[a]\nb = 1");
+    /// ```
+    pub fn trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Set line break style ([LineEnding::Lf] by default)
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Wrap long lines to fit into `width` columns, see [`Ini::wrap_values()`](crate::Ini::wrap_values)
+    pub fn wrap_values(mut self, width: usize) -> Self {
+        self.wrap_width = Some(width);
+        self
+    }
+
+    /// Set quoting of values ([QuoteMode::Never] by default)
+    pub fn quoting(mut self, mode: QuoteMode) -> Self {
+        self.quote_mode = mode;
+        self
+    }
+
+    /// Enable or disable writing of non-ASCII characters as `\uXXXX` sequences (disabled by default)
+    ///
+    /// Output can be read back with [`ParserConfig::unicode_escapes()`](crate::ParserConfig::unicode_escapes).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{FormatOptions, Ini};
+    /// let conf = Ini::new().section("a").item("name", "José");
+    ///
+    /// assert_eq!(conf.to_string_with(&FormatOptions::new().escape_non_ascii(true)), "[a]\nname = Jos\\u00e9\n");
+    /// ```
+    pub fn escape_non_ascii(mut self, enabled: bool) -> Self {
+        self.escape_non_ascii = enabled;
+        self
+    }
+
+    /// String between key and value
+    pub(crate) fn separator(&self) -> String {
+        match (self.delimiter, self.spaces_around_delimiter) {
+            (delimiter, false) => delimiter.to_string(),
+            ('=', true) => " = ".to_owned(),
+            (delimiter, true) => format!("{} ", delimiter),
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        assert_eq!(LineEnding::detect("[a]\r\nb = 1\nc = 2"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("[a]\nb = 1\r\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("[a]"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn separator() {
+        assert_eq!(FormatOptions::new().separator(), " = ");
+        assert_eq!(FormatOptions::new().delimiter(':').separator(), ": ");
+        assert_eq!(FormatOptions::new().delimiter(':').spaces_around_delimiter(false).separator(), ":");
+    }
+}
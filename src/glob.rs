@@ -0,0 +1,47 @@
+//! Glob module
+//!
+//! Contains `glob_match` routine to match section names against patterns with wildcards
+/// check if `name` matches glob `pattern`, where `*` matches any sequence of characters and `?` matches one character
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // position in pattern after the last `*` and position in name it is matched up to
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                star = Some((p, n));
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((after, matched)) => {
+                    p = after;
+                    n = matched + 1;
+                    star = Some((after, n));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("host:*", "host:web1"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b?d", "axxbbcd"));
+        assert!(glob_match("*.log", "a.b.log"));
+        assert!(!glob_match("host:*", "hosts"));
+        assert!(!glob_match("a?", "a"));
+        assert!(!glob_match("*a", "bab"));
+    }
+}
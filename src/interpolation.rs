@@ -0,0 +1,140 @@
+//! Interpolation module
+//!
+//! Contains routines to substitute `${section:key}` references and environment variables in values
+use crate::error::InterpolationError;
+use crate::Ini;
+
+/// Action on reference to environment variable which is not set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnsetEnvPolicy {
+    /// keep reference as is
+    #[default]
+    Keep,
+    /// replace reference with empty string
+    Empty,
+    /// stop with [InterpolationError::UnsetVariable]
+    Error,
+}
+
+/// substitute `$NAME` and `${NAME}` in `value` using `lookup`, `$$` is replaced by `$`.
+/// Returns name of unset variable as error if policy is [UnsetEnvPolicy::Error].
+pub fn expand_env<F>(value: &str, policy: UnsetEnvPolicy, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        let reference = &rest[index..];
+        rest = &rest[index + 1..];
+        let name = if let Some(tail) = rest.strip_prefix('$') {
+            result.push('$');
+            rest = tail;
+            continue;
+        } else if let Some(end) = rest.strip_prefix('{').and_then(|tail| tail.find('}')) {
+            let name = &rest[1..end + 1];
+            rest = &rest[end + 2..];
+            name
+        } else {
+            let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            name
+        };
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            result.push_str(&reference[..reference.len() - rest.len()]);
+            continue;
+        }
+        match (lookup(name), policy) {
+            (Some(value), _) => result.push_str(&value),
+            (None, UnsetEnvPolicy::Keep) => result.push_str(&reference[..reference.len() - rest.len()]),
+            (None, UnsetEnvPolicy::Empty) => (),
+            (None, UnsetEnvPolicy::Error) => return Err(name.to_owned()),
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Resolver of references which remembers the chain of keys being resolved to detect cycles
+pub struct Resolver<'a> {
+    ini: &'a Ini,
+    stack: Vec<(String, String)>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(ini: &'a Ini) -> Resolver<'a> {
+        Resolver { ini, stack: Vec::new() }
+    }
+
+    /// get value of `key` in `section` with all references substituted
+    pub fn resolve(&mut self, section: &str, key: &str) -> Result<String, InterpolationError> {
+        let reference = (section.to_owned(), key.to_owned());
+        if self.stack.contains(&reference) {
+            return Err(InterpolationError::Cycle(reference.0, reference.1));
+        }
+        let raw = match self.ini.get_raw(section, key) {
+            Some(raw) => raw,
+            None => return Err(InterpolationError::MissingReference(reference.0, reference.1)),
+        };
+        self.stack.push(reference);
+        let result = self.substitute(raw, section, key);
+        self.stack.pop();
+        result
+    }
+
+    /// substitute references in `value` of `key` in `section`
+    fn substitute(&mut self, value: &str, section: &str, key: &str) -> Result<String, InterpolationError> {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(index) = rest.find('$') {
+            result.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+            if let Some(tail) = rest.strip_prefix('$') {
+                result.push('$');
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix('{') {
+                let end = tail.find('}').filter(|&end| end > 0);
+                let end = end.ok_or_else(|| InterpolationError::IncorrectSyntax(section.to_owned(), key.to_owned()))?;
+                let (ref_section, ref_key) = match tail[..end].rfind(':') {
+                    Some(colon) => (&tail[..colon], &tail[colon + 1..end]),
+                    None => (section, &tail[..end]),
+                };
+                result.push_str(&self.resolve(ref_section, ref_key)?);
+                rest = &tail[end + 1..];
+            } else {
+                result.push('$');
+            }
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/user".to_owned()),
+            "APP_PORT" => Some("8080".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn env() {
+        let value = "$HOME/app:${APP_PORT}, $$HOME, $1 $ ${} ${UNSET} $UNSET.";
+        assert_eq!(
+            expand_env(value, UnsetEnvPolicy::Keep, lookup),
+            Ok("/home/user/app:8080, $HOME, $1 $ ${} ${UNSET} $UNSET.".to_owned())
+        );
+        assert_eq!(
+            expand_env(value, UnsetEnvPolicy::Empty, lookup),
+            Ok("/home/user/app:8080, $HOME, $1 $ ${}  .".to_owned())
+        );
+        assert_eq!(expand_env(value, UnsetEnvPolicy::Error, lookup), Err("UNSET".to_owned()));
+    }
+}
@@ -0,0 +1,342 @@
+//! JSON module
+//!
+//! Contains conversion of [Ini] to and from two-level JSON objects, enabled with `json` feature
+use crate::escape::decode_unicode;
+use crate::{Error, Ini, ParseError, ParseErrorKind};
+use std::fmt::Write;
+
+impl Ini {
+    /// Convert document to JSON object which maps section names to objects of string values
+    ///
+    /// Keys before the first section header are placed into section with empty name if there are any.
+    /// Comments and formatting are not kept.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[server]\nhost = localhost\nport = 8080\n[empty]").unwrap();
+    ///
+    /// assert_eq!(conf.to_json(), r#"{"server":{"host":"localhost","port":"8080"},"empty":{}}"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut result = String::from("{");
+        let sections = self.document.iter().filter(|(name, section)| !name.is_empty() || !section.is_empty());
+        for (index, (name, section)) in sections.enumerate() {
+            if index > 0 {
+                result.push(',');
+            }
+            write_string(&mut result, name);
+            result.push_str(":{");
+            for (index, (key, value)) in section.values.iter().enumerate() {
+                if index > 0 {
+                    result.push(',');
+                }
+                write_string(&mut result, key);
+                result.push(':');
+                write_string(&mut result, value);
+            }
+            result.push('}');
+        }
+        result.push('}');
+        result
+    }
+
+    /// Build document from JSON object which maps section names to objects of values
+    ///
+    /// Strings are taken as is, numbers and booleans are written as in JSON, `null` becomes empty value
+    /// and arrays of them are joined with `, ` to be read with [`get_vec()`](Ini::get_vec).
+    ///
+    /// # Errors
+    /// [Error::Parse] with [IncorrectSyntax](ParseErrorKind::IncorrectSyntax) error pointing at invalid
+    /// JSON or at value which is not allowed at its level
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let json = r#"{"server": {"host": "localhost", "port": 8080, "tls": false}, "log": {"targets": ["file", "stderr"]}}"#;
+    /// let conf = Ini::from_json(json).unwrap();
+    ///
+    /// assert_eq!(conf.get::<u16>("server", "port"), Some(8080));
+    /// assert_eq!(conf.get_vec::<String>("log", "targets"), Some(vec!["file".to_owned(), "stderr".to_owned()]));
+    ///
+    /// let error = Ini::from_json(r#"{"server": {"port": {}}}"#).unwrap_err();
+    /// assert_eq!(error.to_string(), "Incorrect syntax at line 1, column 21\n{\"server\": {\"port\": {}}}\n                    ^");
+    /// ```
+    pub fn from_json(json: &str) -> Result<Ini, Error> {
+        let mut parser = JsonParser { input: json, position: 0 };
+        let mut result = Ini::new();
+        parser.expect('{')?;
+        if !parser.eat('}') {
+            loop {
+                let name = parser.string()?;
+                parser.expect(':')?;
+                parser.expect('{')?;
+                result.section_mut(name.clone());
+                if !parser.eat('}') {
+                    loop {
+                        let key = parser.string()?;
+                        parser.expect(':')?;
+                        let value = parser.value()?;
+                        result.set(&name, &key, value);
+                        if !parser.eat(',') {
+                            parser.expect('}')?;
+                            break;
+                        }
+                    }
+                }
+                if !parser.eat(',') {
+                    parser.expect('}')?;
+                    break;
+                }
+            }
+        }
+        parser.skip_whitespace();
+        if parser.position < json.len() {
+            return Err(parser.error());
+        }
+        result.modified = false;
+        Ok(result)
+    }
+}
+
+/// Private function which writes `value` as quoted JSON string
+fn write_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Private function which checks that `number` matches JSON grammar, e.g. has no leading zeros
+fn is_number(number: &str) -> bool {
+    fn digits(s: &str) -> usize {
+        s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len()
+    }
+    let rest = number.strip_prefix('-').unwrap_or(number);
+    let integer = digits(rest);
+    if integer == 0 || (integer > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[integer..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        match digits(fraction) {
+            0 => return false,
+            len => rest = &fraction[len..],
+        }
+    }
+    if let Some(exponent) = rest.strip_prefix(&['e', 'E'][..]) {
+        let exponent = exponent.strip_prefix(&['+', '-'][..]).unwrap_or(exponent);
+        match digits(exponent) {
+            0 => return false,
+            len => rest = &exponent[len..],
+        }
+    }
+    rest.is_empty()
+}
+
+/// Private recursive descent parser of JSON input
+struct JsonParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    /// Error pointing at current position
+    fn error(&self) -> Error {
+        let start = self.input[..self.position].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.input[self.position..].find('\n').map_or(self.input.len(), |i| self.position + i);
+        let line = self.input[..start].matches('\n').count() + 1;
+        let text = self.input[start..end].trim_end_matches('\r');
+        let position = (self.position - start).min(text.len());
+        ParseError::new(ParseErrorKind::IncorrectSyntax, line, text, position).shifted(start).into()
+    }
+
+    /// Skip `c` after whitespace if it's the next character
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Parse string, number, boolean, null or array of them into value of key
+    fn value(&mut self) -> Result<String, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.string(),
+            Some('[') => {
+                self.position += 1;
+                let mut items = Vec::new();
+                if !self.eat(']') {
+                    loop {
+                        self.skip_whitespace();
+                        if self.peek() == Some('[') {
+                            return Err(self.error());
+                        }
+                        items.push(self.value()?);
+                        if !self.eat(',') {
+                            self.expect(']')?;
+                            break;
+                        }
+                    }
+                }
+                Ok(items.join(", "))
+            }
+            Some('t') | Some('f') | Some('n') => {
+                for (literal, value) in [("true", "true"), ("false", "false"), ("null", "")] {
+                    if self.input[self.position..].starts_with(literal) {
+                        self.position += literal.len();
+                        return Ok(value.to_owned());
+                    }
+                }
+                Err(self.error())
+            }
+            Some('-' | '0'..='9') => {
+                let rest = &self.input[self.position..];
+                let len =
+                    rest.find(|c: char| !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9')).unwrap_or(rest.len());
+                let number = &rest[..len];
+                if !is_number(number) {
+                    return Err(self.error());
+                }
+                self.position += len;
+                Ok(number.to_owned())
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Parse quoted string with escape sequences
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+        let mut result = String::new();
+        loop {
+            let start = self.position;
+            match self.bump() {
+                Some('"') => return Ok(result),
+                Some('\\') => match self.bump() {
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('/') => result.push('/'),
+                    Some('b') => result.push('\u{8}'),
+                    Some('f') => result.push('\u{c}'),
+                    Some('n') => result.push('\n'),
+                    Some('r') => result.push('\r'),
+                    Some('t') => result.push('\t'),
+                    Some('u') => {
+                        let mut chars = self.input[self.position..].chars();
+                        match decode_unicode(&mut chars) {
+                            Some(c) => {
+                                self.position = self.input.len() - chars.as_str().len();
+                                result.push(c);
+                            }
+                            None => {
+                                self.position = start;
+                                return Err(self.error());
+                            }
+                        }
+                    }
+                    _ => {
+                        self.position = start;
+                        return Err(self.error());
+                    }
+                },
+                Some(c) if (c as u32) >= 0x20 => result.push(c),
+                _ => {
+                    self.position = start;
+                    return Err(self.error());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let ini = Ini::new().section("").item("top", 1).section("a \"b\"").item("c", "line\\\n\ttab\u{1}é😀");
+        let json = ini.to_json();
+        assert_eq!(json, r#"{"":{"top":"1"},"a \"b\"":{"c":"line\\\n\ttab\u0001é😀"}}"#);
+        assert_eq!(Ini::from_json(&json).unwrap(), ini);
+        assert_eq!(Ini::new().to_json(), "{}");
+    }
+
+    #[test]
+    fn parse() {
+        let ini = Ini::from_json(" {\"a\" : {\"n\": -1.5e3, \"x\": null, \"e\": \"\\ud83d\\ude00\\/\", \"v\": []} } ")
+            .unwrap();
+        assert_eq!(ini.get_raw("a", "n"), Some("-1.5e3"));
+        assert_eq!(ini.get_raw("a", "x"), Some(""));
+        assert_eq!(ini.get_raw("a", "e"), Some("😀/"));
+        assert_eq!(ini.get_raw("a", "v"), Some(""));
+        assert!(!ini.is_modified());
+        for number in ["0", "-0", "0.5", "10", "-1.25E+2", "0e-1"] {
+            let ini = Ini::from_json(&format!("{{\"a\": {{\"n\": {}}}}}", number)).unwrap();
+            assert_eq!(ini.get_raw("a", "n"), Some(number));
+        }
+        for (json, line, column) in [
+            ("", 1, 1),
+            ("{\"a\": 1}", 1, 7),
+            ("{\"a\": {\"b\": [[1]]}}", 1, 14),
+            ("{\"a\": {\"b\": tru}}", 1, 13),
+            ("{\"a\": {\"b\": +1}}", 1, 13),
+            ("{\"a\": {\"b\": 007}}", 1, 13),
+            ("{\"a\": {\"b\": -01.5}}", 1, 13),
+            ("{\"a\": {\"b\": 1.}}", 1, 13),
+            ("{\"a\": {\"b\": 1e}}", 1, 13),
+            ("{\"a\": {\"b\": \"\\ud83d\"}}", 1, 14),
+            ("{\"a\": {\"b\": \"\\ud83d\\u0041\"}}", 1, 14),
+            ("{\"a\": {\"b\": \"\\ude00\"}}", 1, 14),
+            ("{\"a\": {\"b\": \"\\u12\"}}", 1, 14),
+            ("{\"a\": {\"b\": \"\\x\"}}", 1, 14),
+            ("{\n\"a\": {}\n} x", 3, 3),
+            ("{\"a\": {},}", 1, 10),
+        ] {
+            match Ini::from_json(json) {
+                Err(Error::Parse(error)) => assert_eq!((error.line(), error.column()), (line, column), "{}", json),
+                other => panic!("{}: {:?}", json, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Layered module
+//!
+//! Contains [Layered] configuration which resolves values through several documents
+use crate::{value, FromIniValue, Ini, MergePolicy};
+
+/// Stack of named documents, e.g. defaults, system, user and runtime overrides
+///
+/// Getters look for the key in the last added layer first and fall back to previous ones,
+/// so later layers take precedence. [`source()`](Layered::source) tells which layer supplied a value.
+///
+/// Keys set explicitly in any layer take precedence over [default sections](Ini::default_section) of all layers,
+/// which are searched in the same order only if no layer has the key. Like in [Ini], default section of a layer
+/// applies only to sections present in that layer.
+///
+/// # Example
+/// ```
+/// # use tini::{Ini, Layered};
+/// let defaults = Ini::from_string("[server]\nhost = 0.0.0.0\nport = 80").unwrap();
+/// let user = Ini::from_string("[server]\nport = 8080").unwrap();
+///
+/// let conf = Layered::new().layer("defaults", defaults).layer("user", user);
+///
+/// assert_eq!(conf.get::<u16>("server", "port"), Some(8080));
+/// assert_eq!(conf.source("server", "port"), Some("user"));
+/// assert_eq!(conf.get_raw("server", "host"), Some("0.0.0.0"));
+/// assert_eq!(conf.source("server", "host"), Some("defaults"));
+/// assert_eq!(conf.source("server", "other"), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Layered {
+    layers: Vec<(String, Ini)>,
+}
+
+impl Layered {
+    /// Create stack without layers (similar to [Layered::default])
+    pub fn new() -> Layered {
+        Layered { layers: Vec::new() }
+    }
+
+    /// Add document `ini` with `name` on top of existing layers
+    pub fn layer(mut self, name: &str, ini: Ini) -> Self {
+        self.layers.push((name.to_owned(), ini));
+        self
+    }
+
+    /// Iterate over layer names from the lowest to the highest precedence
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Get document of layer with `name`
+    pub fn get_layer(&self, name: &str) -> Option<&Ini> {
+        self.layers.iter().find(|(n, _)| n == name).map(|(_, ini)| ini)
+    }
+
+    /// Get mutable document of layer with `name`, e.g. to apply runtime overrides
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, Layered};
+    /// let mut conf = Layered::new().layer("defaults", Ini::new().section("a").item("b", 1)).layer("runtime", Ini::new());
+    ///
+    /// conf.get_layer_mut("runtime").unwrap().set("a", "b", 2);
+    /// assert_eq!(conf.get::<u8>("a", "b"), Some(2));
+    /// ```
+    pub fn get_layer_mut(&mut self, name: &str) -> Option<&mut Ini> {
+        self.layers.iter_mut().find(|(n, _)| n == name).map(|(_, ini)| ini)
+    }
+
+    /// Private method which finds layer with `key` in `section`, returning layer name and value
+    ///
+    /// Explicit keys of all layers are checked before falling back to default sections.
+    fn lookup(&self, section: &str, key: &str) -> Option<(&str, &str)> {
+        let layers = || self.layers.iter().rev().map(|(name, ini)| (name.as_str(), ini));
+        layers()
+            .find_map(|(name, ini)| ini.get_section(section).and_then(|s| s.get_raw(key)).map(|value| (name, value)))
+            .or_else(|| layers().find_map(|(name, ini)| ini.get_raw(section, key).map(|value| (name, value))))
+    }
+
+    /// Name of layer which supplies value of `key` in `section`
+    pub fn source(&self, section: &str, key: &str) -> Option<&str> {
+        self.lookup(section, key).map(|(name, _)| name)
+    }
+
+    /// Get value of `key` in `section` as is, see [`Ini::get_raw()`]
+    pub fn get_raw(&self, section: &str, key: &str) -> Option<&str> {
+        self.lookup(section, key).map(|(_, value)| value)
+    }
+
+    /// Get scalar value of `key` in `section`, see [`Ini::get()`]
+    ///
+    /// Value is taken from the highest layer with the key even if it can't be parsed.
+    pub fn get<T>(&self, section: &str, key: &str) -> Option<T>
+    where
+        T: FromIniValue,
+    {
+        self.get_raw(section, key).and_then(|x| T::parse_value(x))
+    }
+
+    /// Get vector value of `key` in `section` separated by `,`, see [`Ini::get_vec()`]
+    pub fn get_vec<T>(&self, section: &str, key: &str) -> Option<Vec<T>>
+    where
+        T: FromIniValue,
+    {
+        self.get_raw(section, key)
+            .and_then(|x| x.split(',').map(|s| T::parse_value(s.trim())).collect::<Option<Vec<T>>>())
+    }
+
+    /// Get boolean value of `key` in `section`, see [`Ini::get_bool()`]
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        self.get_raw(section, key).and_then(value::parse_bool)
+    }
+
+    /// Merge all layers into a single document
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, Layered};
+    /// let conf = Layered::new()
+    ///     .layer("defaults", Ini::new().section("a").item("x", 1).item("y", 1))
+    ///     .layer("user", Ini::new().section("a").item("y", 2).section("b").item("z", 3));
+    ///
+    /// assert_eq!(conf.flatten().to_string(), "[a]\nx = 1\ny = 2\n\n[b]\nz = 3\n");
+    /// ```
+    pub fn flatten(&self) -> Ini {
+        self.layers.iter().fold(Ini::new(), |result, (_, ini)| {
+            result.merge(ini.clone(), MergePolicy::OverwriteExisting).expect("overwriting merge has no conflicts")
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layers() -> Layered {
+        Layered::new()
+            .layer("defaults", Ini::from_string("[a]\nx = 1\ny = 1\nv = 1, 2\n[b]\nz = 1").unwrap())
+            .layer("system", Ini::from_string("[a]\ny = 2\nflag = yes").unwrap())
+            .layer("user", Ini::from_string("[a]\ny = three").unwrap())
+    }
+
+    #[test]
+    fn precedence() {
+        let conf = layers();
+        assert_eq!(conf.names().collect::<Vec<_>>(), ["defaults", "system", "user"]);
+        assert_eq!(conf.get::<u8>("a", "x"), Some(1));
+        assert_eq!(conf.get_raw("a", "y"), Some("three"));
+        assert_eq!(conf.get::<u8>("a", "y"), None);
+        assert_eq!(conf.get_vec::<u8>("a", "v"), Some(vec![1, 2]));
+        assert_eq!(conf.get_bool("a", "flag"), Some(true));
+        assert_eq!(conf.get::<u8>("b", "z"), Some(1));
+        assert_eq!(conf.get_raw("c", "x"), None);
+        assert_eq!(conf.flatten().to_string(), "[a]\nx = 1\ny = three\nv = 1, 2\nflag = yes\n\n[b]\nz = 1\n");
+        assert_eq!(Layered::new().get_raw("a", "x"), None);
+    }
+
+    #[test]
+    fn source() {
+        let conf = layers();
+        assert_eq!(conf.source("a", "x"), Some("defaults"));
+        assert_eq!(conf.source("a", "y"), Some("user"));
+        assert_eq!(conf.source("a", "flag"), Some("system"));
+        assert_eq!(conf.source("a", "missing"), None);
+        assert_eq!(conf.source("missing", "x"), None);
+    }
+
+    #[test]
+    fn get_layer_mut() {
+        let mut conf = layers();
+        assert!(conf.get_layer_mut("missing").is_none());
+        conf.get_layer_mut("system").unwrap().set("a", "x", 5);
+        assert_eq!((conf.get::<u8>("a", "x"), conf.source("a", "x")), (Some(5), Some("system")));
+        conf.get_layer_mut("user").unwrap().set("a", "y", 7);
+        assert_eq!(conf.get::<u8>("a", "y"), Some(7));
+        assert_eq!(conf.get_layer("defaults").and_then(|ini| ini.get::<u8>("a", "x")), Some(1));
+    }
+
+    #[test]
+    fn default_section() {
+        let base = Ini::from_string("[server]\nport = 80\n[db]\nport = 5432").unwrap();
+        let user = Ini::builder()
+            .default_section("DEFAULT")
+            .parse_str("[DEFAULT]\nport = 8080\nuser = admin\n[server]\n[db]\n[cache]")
+            .unwrap();
+        let conf = Layered::new().layer("base", base).layer("user", user);
+        assert_eq!(conf.get::<u16>("server", "port"), Some(80));
+        assert_eq!(conf.source("server", "port"), Some("base"));
+        assert_eq!(conf.get::<u16>("db", "port"), Some(5432));
+        assert_eq!(conf.get::<u16>("cache", "port"), Some(8080));
+        assert_eq!(conf.source("cache", "port"), Some("user"));
+        assert_eq!(conf.get_raw("db", "user"), Some("admin"));
+        assert_eq!(conf.source("db", "user"), Some("user"));
+
+        let base = Ini::builder().default_section("DEFAULT").parse_str("[DEFAULT]\nhost = localhost\n[db]").unwrap();
+        let user = Ini::from_string("[db]\nport = 5432\n[cache]").unwrap();
+        let conf = Layered::new().layer("base", base).layer("user", user);
+        assert_eq!(conf.get_raw("db", "host"), Some("localhost"));
+        assert_eq!(conf.source("db", "host"), Some("base"));
+        assert_eq!(conf.get_raw("cache", "host"), None);
+    }
+}
@@ -4,12 +4,18 @@
 //!
 //! Features:
 //!
-//! * no dependencies;
+//! * no dependencies by default;
 //! * parsing [from file](Ini::from_file), [from reader](Ini::from_reader) and [from string](Ini::from_string);
+//! * [configurable parsing options](Ini::builder);
 //! * [convert parsed value to given type](Ini::get);
 //! * [parse comma-separated lists to vectors](Ini::get_vec);
 //! * construct new ini-structure with [method chaining](Ini::item);
-//! * writing [to file](Ini::to_file), [to writer](Ini::to_writer) and [to string](Ini#impl-Display).
+//! * writing [to file](Ini::to_file), [to writer](Ini::to_writer) and [to string](Ini#impl-Display);
+//! * [lossless editing](Document) which keeps comments and formatting;
+//! * [streaming parser](IniEvents) for large inputs;
+//! * [mapping to structures](FromIni) with optional `derive` feature;
+//! * conversion to and from JSON objects with optional `json` feature;
+//! * [serde](https://serde.rs) support and [bridging of structures](from_ini) with optional `serde` feature.
 //!
 //! # Examples
 //! ## Read from buffer and get string values
@@ -39,45 +45,407 @@
 //! assert_eq!(consts, [3.1416, 2.7183]);
 //! assert_eq!(lost, [4, 8, 15, 16, 23, 42]);
 //! ````
+mod atomic;
+mod config;
+mod diff;
+mod document;
+mod encoding;
 mod error;
+mod escape;
+mod events;
+mod format;
+mod glob;
+mod interpolation;
+#[cfg(feature = "json")]
+mod json;
+mod layered;
+mod macros;
+mod mapping;
+mod merge;
 mod ordered_hashmap;
 mod parser;
+mod section;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod value;
 
-pub use error::{Error, ParseError};
+pub use config::{DuplicateKeyPolicy, DuplicateSectionPolicy, EntryAction, ParsedEntry, ParserConfig, SectionAction};
+pub use diff::{Change, IniDiff};
+pub use document::Document;
+pub use encoding::Encoding;
+pub use error::{
+    Error, GetError, InterpolationError, MappingError, MergeError, OverrideError, ParseError, ParseErrorKind,
+    ParseIssue, ValidationError,
+};
+pub use escape::QuoteMode;
+pub use events::{Event, IniEvents};
+pub use format::{FormatOptions, LineEnding};
+use glob::glob_match;
+pub use interpolation::UnsetEnvPolicy;
+use interpolation::{expand_env, Resolver};
+pub use layered::Layered;
+#[doc(hidden)]
+pub use mapping::__private;
+pub use mapping::{FromIni, FromSection, ToIni, ToSection};
+pub use merge::MergePolicy;
 use ordered_hashmap::OrderedHashMap;
-use parser::{parse_line, Parsed};
+use parser::{indentation, join_subsection, line_content, parse_line, split_comment, split_subsection, Lines, Parsed};
+pub use section::{KeyEntry, Section, SectionEntry};
+#[cfg(feature = "serde")]
+pub use serde_impl::{from_ini, to_ini, SerdeError};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt;
 use std::fs::File;
-use std::io::{self, BufReader, BufWriter, Read, Write};
-use std::iter::Iterator;
-use std::path::Path;
+use std::io::{self, BufReader, Read, Write};
+use std::iter::{FromIterator, Iterator};
+use std::ops::{Index, IndexMut};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+#[cfg(feature = "derive")]
+pub use tini_derive::{FromIni, FromSection, ToIni, ToSection};
+pub use value::{FromIniValue, IniValue, ToIniValue};
 
 /// Structure for INI-file data
-#[derive(Debug)]
+///
+/// Documents are equal if they have the same sections, keys and values regardless of order,
+/// comments and output style. Use [`eq_ordered()`](Ini::eq_ordered) to compare order too.
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let a = Ini::from_string("[a]\nx = 1 ; one\n[b]\ny = 2").unwrap();
+/// let b = Ini::new().section("b").item("y", 2).section("a").item("x", 1);
+///
+/// assert_eq!(a, b);
+/// assert!(!a.eq_ordered(&b));
+/// assert!(a.eq_ordered(&a.clone()));
+/// ```
+#[derive(Debug, Clone)]
 pub struct Ini {
     #[doc(hidden)]
     document: OrderedHashMap<String, Section>,
     last_section_name: String,
     empty_section: Section,
+    inline_comments: HashMap<String, HashMap<String, String>>,
+    section_comments: HashMap<String, String>,
+    leading_comments: HashMap<(String, Option<String>), Vec<String>>,
+    list_separators: HashMap<String, HashMap<String, String>>,
+    preamble: Option<String>,
+    last_key: Option<String>,
+    global_section: String,
+    default_section: Option<String>,
+    bom: bool,
+    case_insensitive: bool,
+    format: FormatOptions,
+    fsync: bool,
+    modified: bool,
+    source: Option<PathBuf>,
 }
 
 impl Ini {
     /// Create an empty Ini (similar to [Ini::default])
     pub fn new() -> Ini {
-        Ini { document: OrderedHashMap::new(), last_section_name: String::new(), empty_section: Section::new() }
+        Ini {
+            document: OrderedHashMap::new(),
+            last_section_name: String::new(),
+            empty_section: Section::new(),
+            inline_comments: HashMap::new(),
+            section_comments: HashMap::new(),
+            leading_comments: HashMap::new(),
+            list_separators: HashMap::new(),
+            preamble: None,
+            last_key: None,
+            global_section: String::new(),
+            default_section: None,
+            bom: false,
+            case_insensitive: false,
+            format: FormatOptions::new(),
+            fsync: false,
+            modified: false,
+            source: None,
+        }
+    }
+
+    /// Create an empty Ini with space for at least `sections` sections
+    ///
+    /// Use [`Section::reserve()`] to reserve space for keys of a section.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::with_capacity(1000);
+    /// for table in 0..1000 {
+    ///     let section = conf.entry(&format!("table{}", table)).or_default();
+    ///     section.reserve(2);
+    ///     section.insert("rows", table * 10);
+    ///     section.insert("size", table * 100);
+    /// }
+    ///
+    /// assert_eq!(conf.len(), 1000);
+    /// ```
+    pub fn with_capacity(sections: usize) -> Ini {
+        Ini { document: OrderedHashMap::with_capacity(sections), ..Ini::new() }
+    }
+
+    /// Create default [ParserConfig] to construct Ini with custom parsing options
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::builder().comment_chars("#").parse_str("[a]\nb = 1;2 # comment").unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// assert_eq!(b, Some("1;2".to_owned()));
+    /// ```
+    pub fn builder() -> ParserConfig {
+        ParserConfig::new()
     }
 
     /// Private construct method which creaate [Ini] struct from input string
-    fn parse(string: &str) -> Result<Ini, Error> {
-        let mut result = Ini::new();
-        for (index, line) in string.lines().enumerate() {
-            match parse_line(&line, index + 1)? {
-                Parsed::Section(name) => result = result.section(name),
-                Parsed::Value(name, value) => result = result.item(name, value),
+    pub(crate) fn parse(string: &str, config: &ParserConfig) -> Result<Ini, Error> {
+        Ini::parse_with_issues(string, config, None)
+    }
+
+    /// Construct Ini from string skipping malformed lines
+    ///
+    /// Unlike [from_string](Ini::from_string) parsing is not stopped on the first error: every malformed line
+    /// is reported as [ParseIssue] and ignored. Duplicate sections are merged and only the first of duplicate
+    /// keys is kept when duplicates are not allowed by configuration.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, ParseErrorKind};
+    /// let (conf, issues) = Ini::parse_lossy("[a]\nb = 1\n[broken\nc = 2\n= 3");
+    ///
+    /// assert_eq!(conf.get::<u8>("a", "c"), Some(2));
+    /// assert_eq!(issues.len(), 2);
+    /// assert_eq!(issues[0].error.kind(), ParseErrorKind::IncorrectSection);
+    /// assert_eq!(issues[0].line, 3);
+    /// assert_eq!(issues[1].text, "= 3");
+    /// ```
+    pub fn parse_lossy(buf: &str) -> (Ini, Vec<ParseIssue>) {
+        ParserConfig::default().parse_lossy(buf)
+    }
+
+    /// Parse string collecting errors into `issues` if it's given, or stopping on the first error otherwise
+    pub(crate) fn parse_with_issues(
+        string: &str, config: &ParserConfig, mut issues: Option<&mut Vec<ParseIssue>>,
+    ) -> Result<Ini, Error> {
+        // report error and skip current line in lossy mode
+        macro_rules! fail {
+            ($error:expr) => {{
+                let error: ParseError = $error;
+                match issues.as_mut() {
+                    Some(issues) => {
+                        issues.push(ParseIssue { line: error.line(), text: error.text().trim().to_owned(), error });
+                    }
+                    None => return Err(error.into()),
+                }
+            }};
+        }
+        let mut result = Ini { case_insensitive: config.case_insensitive, ..Ini::new() };
+        if let Err(error) = config.check_input_size(string.as_bytes()) {
+            fail!(error);
+            return Ok(result);
+        }
+        result.global_section = result.normalize(&config.global_section).into_owned();
+        result = result.section(config.global_section.as_str());
+        result.default_section = config.default_section.as_deref().map(|name| result.normalize(name).into_owned());
+        // byte order mark is not a part of the first line
+        let bom_len = if string.starts_with(BOM) { BOM.len() } else { 0 };
+        let string = match string.strip_prefix(BOM) {
+            Some(stripped) => {
+                result.bom = true;
+                stripped
+            }
+            None => string,
+        };
+        result.format.line_ending = LineEnding::detect(string);
+        // key and its indentation which may be continued on the following lines,
+        // key is None if continuation should be discarded
+        let mut last_key: Option<(Option<String>, usize)> = None;
+        // names of sections which headers were already parsed
+        let mut headers = HashSet::new();
+        // number of parsed key-value lines
+        let mut keys = 0;
+        // comment lines at the start of input are collected into preamble
+        let mut preamble: Option<Vec<String>> = Some(Vec::new());
+        // keys of section skipped by hook are ignored up to the next header
+        let mut skip_section = false;
+        for (index, offset, line) in Lines::new(string, config) {
+            let error_at = |kind| ParseError::new(kind, index, &line, indentation(&line)).shifted(bom_len + offset);
+            if let Some(max) = config.max_line_length.filter(|&max| line.len() > max) {
+                // limit errors stop parsing even in lossy mode
+                let position = (0..=max).rev().find(|&i| line.is_char_boundary(i)).unwrap_or(0);
+                fail!(ParseError::new(ParseErrorKind::LineTooLong, index, &line, position).shifted(bom_len + offset));
+                break;
+            }
+            if let Some((key, indent)) = last_key.as_ref().filter(|_| config.multiline_values) {
+                if line.trim().is_empty() {
+                    last_key = None;
+                } else if let Some(content) = line_content(&line, config).filter(|_| indentation(&line) > *indent) {
+                    let section = result.document.get_mut(&result.last_section_name);
+                    if let Some(value) = key.as_ref().and_then(|key| section.and_then(|s| s.values.get_mut(key))) {
+                        if !value.is_empty() {
+                            value.push('\n');
+                        }
+                        value.push_str(content);
+                    }
+                    continue;
+                }
+            }
+            let parsed = match parse_line(&line, index, config) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    fail!(error.shifted(bom_len + offset));
+                    last_key = None;
+                    continue;
+                }
+            };
+            if let Some(lines) = preamble.as_mut() {
+                match split_comment(&line, config).1.filter(|_| line_content(&line, config).is_none()) {
+                    Some(comment) if config.unicode_escapes => {
+                        lines.push(escape::unescape_unicode(comment.trim()).into())
+                    }
+                    Some(comment) => lines.push(comment.trim().to_owned()),
+                    None => result.preamble = preamble.take().filter(|l| !l.is_empty()).map(|l| l.join("\n")),
+                }
+            }
+            match parsed {
+                Parsed::Section(name, comment) => {
+                    let mut name = result.normalize(&name).into_owned();
+                    last_key = None;
+                    skip_section = false;
+                    if let Some(hook) = config.section_hook.as_ref() {
+                        match (hook.0)(&name) {
+                            SectionAction::Keep => (),
+                            SectionAction::Rename(new_name) => name = result.normalize(&new_name).into_owned(),
+                            SectionAction::Skip => {
+                                skip_section = true;
+                                continue;
+                            }
+                            SectionAction::Reject => {
+                                fail!(error_at(ParseErrorKind::Rejected));
+                                skip_section = true;
+                                continue;
+                            }
+                        }
+                    }
+                    if !headers.contains(&name) && config.max_sections.is_some_and(|max| headers.len() >= max) {
+                        fail!(error_at(ParseErrorKind::TooManySections));
+                        break;
+                    }
+                    if !headers.insert(name.clone()) {
+                        match config.duplicate_section_policy() {
+                            DuplicateSectionPolicy::Merge => (),
+                            DuplicateSectionPolicy::Replace => {
+                                result.document.insert(name.clone(), Section::with_case(config.case_insensitive));
+                                result.inline_comments.remove(&name);
+                                result.section_comments.remove(&name);
+                            }
+                            DuplicateSectionPolicy::Error => fail!(error_at(ParseErrorKind::DuplicateSection)),
+                        }
+                    }
+                    if let Some(comment) = comment {
+                        result.section_comments.insert(name.clone(), comment);
+                    }
+                    // section without keys is kept to be found by has_section()
+                    result.section_mut(name.clone());
+                    result = result.section(name)
+                }
+                Parsed::Value(name, mut value, comment) => {
+                    if skip_section {
+                        last_key = Some((None, indentation(&line)));
+                        continue;
+                    }
+                    if config.strict && headers.is_empty() {
+                        fail!(error_at(ParseErrorKind::KeyOutsideSection));
+                        last_key = None;
+                        continue;
+                    }
+                    keys += 1;
+                    if config.max_keys.is_some_and(|max| keys > max) {
+                        fail!(error_at(ParseErrorKind::TooManyKeys));
+                        break;
+                    }
+                    // values of `key[]` are always collected
+                    let (name, policy) = match name.strip_suffix("[]").filter(|_| config.array_keys) {
+                        Some(name) => (name.trim_end().to_owned(), DuplicateKeyPolicy::CollectIntoVec),
+                        None => (name, config.duplicate_key_policy()),
+                    };
+                    let mut name = result.normalize(&name).into_owned();
+                    if let Some(hook) = config.entry_hook.as_ref() {
+                        let duplicate = result
+                            .document
+                            .get(&result.last_section_name)
+                            .is_some_and(|s| s.values.get(&name).is_some());
+                        let entry = ParsedEntry {
+                            section: &result.last_section_name,
+                            key: &name,
+                            value: &value,
+                            line: index,
+                            duplicate,
+                        };
+                        match (hook.0)(&entry) {
+                            EntryAction::Keep => (),
+                            EntryAction::Replace(new_name, new_value) => {
+                                name = result.normalize(&new_name).into_owned();
+                                value = new_value;
+                            }
+                            EntryAction::Skip => {
+                                last_key = Some((None, indentation(&line)));
+                                continue;
+                            }
+                            EntryAction::Reject => {
+                                fail!(error_at(ParseErrorKind::Rejected));
+                                last_key = Some((None, indentation(&line)));
+                                continue;
+                            }
+                        }
+                    }
+                    let section = result.document.get_mut(&result.last_section_name);
+                    let existing = section.and_then(|s| s.values.get_mut(&name));
+                    if let Some(existing) = existing {
+                        match policy {
+                            DuplicateKeyPolicy::KeepFirst => {
+                                last_key = Some((None, indentation(&line)));
+                                continue;
+                            }
+                            DuplicateKeyPolicy::Error => {
+                                fail!(error_at(ParseErrorKind::DuplicateKey));
+                                last_key = Some((None, indentation(&line)));
+                                continue;
+                            }
+                            DuplicateKeyPolicy::CollectIntoVec => {
+                                existing.push_str(", ");
+                                existing.push_str(&value);
+                                last_key = Some((Some(name), indentation(&line)));
+                                continue;
+                            }
+                            DuplicateKeyPolicy::KeepLast => (),
+                        }
+                    }
+                    last_key = Some((Some(name.clone()), indentation(&line)));
+                    if let Some(comment) = comment {
+                        result
+                            .inline_comments
+                            .entry(result.last_section_name.clone())
+                            .or_default()
+                            .insert(name.clone(), comment);
+                    }
+                    result = result.item(name, value)
+                }
                 _ => (),
             };
         }
+        // input consists of comments only
+        if let Some(lines) = preamble.filter(|l| !l.is_empty()) {
+            result.preamble = Some(lines.join("\n"));
+        }
+        result.modified = false;
         Ok(result)
     }
 
@@ -113,7 +481,15 @@ impl Ini {
     {
         let file = File::open(path)?;
         let mut reader = BufReader::new(file);
-        Ini::from_reader(&mut reader)
+        let mut result = Ini::from_reader(&mut reader)?;
+        result.source = Some(path.as_ref().to_owned());
+        Ok(result)
+    }
+
+    /// Path of file the document was loaded from with [`from_file()`](Ini::from_file)
+    /// or [`ParserConfig::parse_file()`]
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source.as_deref()
     }
 
     /// Construct Ini from any struct who implement [Read](std::io::Read) trait
@@ -137,9 +513,7 @@ impl Ini {
     where
         R: Read,
     {
-        let mut buffer = String::new();
-        reader.read_to_string(&mut buffer)?;
-        Ini::parse(&buffer)
+        ParserConfig::default().parse_reader(reader)
     }
 
     /// Construct Ini from any type of string which can be [Into]ed to String
@@ -159,24 +533,142 @@ impl Ini {
     where
         S: Into<String>,
     {
-        Ini::parse(&buf.into())
+        Ini::parse(&buf.into(), &ParserConfig::default())
+    }
+
+    /// Construct Ini from bytes in given encoding
+    ///
+    /// Byte order mark of UTF-16 input is handled like UTF-8 one, see [`bom()`](Ini::bom).
+    ///
+    /// # Errors
+    /// This function will return an [Error] if bytes cannot be decoded or parsed
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Encoding, Ini};
+    /// let bytes = b"[\xf0\xe0\xe7\xe4\xe5\xeb]\n\xea\xeb\xfe\xf7 = 1";
+    ///
+    /// let conf = Ini::from_bytes_with_encoding(bytes, Encoding::Windows1251).unwrap();
+    ///
+    /// let value: Option<u8> = conf.get("раздел", "ключ");
+    /// assert_eq!(value, Some(1));
+    /// assert_eq!(conf.to_bytes_with_encoding(Encoding::Windows1251).unwrap(), b"[\xf0\xe0\xe7\xe4\xe5\xeb]\n\xea\xeb\xfe\xf7 = 1\n");
+    /// ```
+    pub fn from_bytes_with_encoding(bytes: &[u8], encoding: Encoding) -> Result<Ini, Error> {
+        ParserConfig::default().parse_bytes(bytes, encoding)
+    }
+
+    /// Write Ini to bytes in given encoding. Byte order mark is written only for UTF encodings.
+    ///
+    /// # Errors
+    /// This function will return [io::Error] with [InvalidData](io::ErrorKind::InvalidData) kind
+    /// if some characters can not be represented in `encoding`
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Encoding, Ini};
+    /// let conf = Ini::new().section("a").item("b", "é").bom(true);
+    ///
+    /// assert_eq!(conf.to_bytes_with_encoding(Encoding::Latin1).unwrap(), b"[a]\nb = \xe9\n");
+    /// assert_eq!(conf.to_bytes_with_encoding(Encoding::Utf16Be).unwrap()[..4], [0xfe, 0xff, 0, b'[']);
+    /// assert!(conf.section("a").item("c", "€").to_bytes_with_encoding(Encoding::Latin1).is_err());
+    /// ```
+    pub fn to_bytes_with_encoding(&self, encoding: Encoding) -> Result<Vec<u8>, io::Error> {
+        let bom = match encoding {
+            Encoding::Utf8 | Encoding::Utf16Le | Encoding::Utf16Be if self.bom => BOM,
+            _ => "",
+        };
+        encoding.encode(&format!("{}{}", bom, self))
     }
 
     /// Write Ini to file. This function is similar to [from_file](Ini::from_file) in use.
     ///
+    /// File is replaced atomically: data is written to temporary file in the same directory, which is renamed
+    /// over the target afterwards, so a crash can't leave a truncated file. Use [`fsync()`](Ini::fsync) to
+    /// flush data to disk before rename.
+    ///
     /// # Errors
-    /// Errors returned by [File::create] and [Write::write_all]
+    /// Errors returned by [File::create], [Write::write_all] and [fs::rename](std::fs::rename)
     pub fn to_file<S>(&self, path: &S) -> Result<(), io::Error>
     where
         S: AsRef<Path> + ?Sized,
     {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-        self.to_writer(&mut writer)
+        atomic::write(path.as_ref(), self.fsync, |writer| self.to_writer(writer))
+    }
+
+    /// Check if sections, keys, values or comments were changed since document was parsed or saved
+    /// with [`save_if_changed()`](Ini::save_if_changed)
+    ///
+    /// Changes of output style are not tracked. Document is considered changed after
+    /// [`iter_mut()`](Ini::iter_mut) call, even if no values were changed through the iterator.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[a]\nb = 1").unwrap();
+    /// assert!(!conf.is_modified());
+    ///
+    /// let conf = conf.section("a").erase("c");
+    /// assert!(!conf.is_modified());
+    ///
+    /// let conf = conf.section("a").item("b", 2);
+    /// assert!(conf.is_modified());
+    /// ```
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Write Ini to file with [`to_file()`](Ini::to_file) only if document was changed, see
+    /// [`is_modified()`](Ini::is_modified). File is also not rewritten if it already has the same content.
+    ///
+    /// Returns `true` if file was written.
+    ///
+    /// # Errors
+    /// Errors returned by [`to_file()`](Ini::to_file)
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_file("app.ini").unwrap();
+    ///
+    /// assert!(!conf.save_if_changed("app.ini").unwrap());
+    /// ```
+    pub fn save_if_changed<S>(&mut self, path: &S) -> Result<bool, io::Error>
+    where
+        S: AsRef<Path> + ?Sized,
+    {
+        if !self.modified {
+            return Ok(false);
+        }
+        let mut content = Vec::new();
+        self.to_writer(&mut content)?;
+        let changed = std::fs::read(path).map_or(true, |existing| existing != content);
+        if changed {
+            atomic::write(path.as_ref(), self.fsync, |writer| writer.write_all(&content))?;
+        }
+        self.modified = false;
+        Ok(changed)
+    }
+
+    /// Enable or disable flushing data to disk in [`to_file()`](Ini::to_file) before replacing file
+    /// (disabled by default)
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use tini::Ini;
+    /// let conf = Ini::new().section("a").item("b", 1).fsync(true);
+    ///
+    /// conf.to_file("output.ini").unwrap();
+    /// ```
+    pub fn fsync(mut self, enabled: bool) -> Self {
+        self.fsync = enabled;
+        self
     }
 
     /// Write [Ini] to any struct who implement [Write] trait.
     ///
+    /// Output starts with UTF-8 byte order mark if it was present in the parsed input or enabled with [`bom()`](Ini::bom).
+    ///
     /// # Errors
     /// Errors returned by [Write::write_all](Write::write_all)
     ///
@@ -198,254 +690,2028 @@ impl Ini {
     where
         W: Write,
     {
-        writer.write_all(self.to_string().as_bytes())?;
-        Ok(())
+        self.to_writer_with(&self.format, writer)
     }
 
-    /// Set section name for the following methods in chain ([`item()`](Ini::item), [`items()`](Ini::items), etc.)
+    /// Like [`to_writer()`](Ini::to_writer), but with given output style instead of the document one
     ///
-    /// # Warning
-    /// This function doesn't create a section.
+    /// # Errors
+    /// Errors returned by [Write::write_all]
     ///
     /// # Example
     /// ```
-    /// # use tini::Ini;
-    /// let mut conf = Ini::new().section("empty");
-    /// assert_eq!(conf.to_string(), "");
+    /// # use tini::{FormatOptions, Ini};
+    /// let conf = Ini::new().section("a").item("b", 1);
+    /// let mut output = Vec::new();
     ///
-    /// // but section will be created on item() call
-    /// conf = conf.section("one").item("a", 1);
-    /// assert_eq!(conf.to_string(), "[one]\na = 1\n");
+    /// conf.to_writer_with(&FormatOptions::new().delimiter(':'), &mut output).unwrap();
+    /// assert_eq!(output, b"[a]\nb: 1\n");
     /// ```
-    pub fn section<S>(mut self, name: S) -> Self
+    pub fn to_writer_with<W>(&self, options: &FormatOptions, writer: &mut W) -> Result<(), io::Error>
     where
-        S: Into<String>,
+        W: Write,
     {
-        self.last_section_name = name.into();
-        self
+        if self.bom {
+            writer.write_all(BOM.as_bytes())?;
+        }
+        write!(writer, "{}", Formatted(self, options))
     }
 
-    /// Add key-value pair to the end of section, specified in last [`section()`](Ini::section) call,
-    /// or replace value if key already in section
-    ///
-    /// - `name` must support [Into] to [String]
-    /// - `value` must support [Display](fmt::Display) to support conversion to [String]
+    /// Format document with given output style instead of the document one
     ///
     /// # Example
     /// ```
-    /// # use tini::Ini;
-    /// let mut conf = Ini::new().section("test")
-    ///                      .item("value", 10);
+    /// # use tini::{FormatOptions, Ini};
+    /// let conf = Ini::new().section("a").item("b", 1).section("c").item("d", 2);
     ///
-    /// assert_eq!(conf.to_string(), "[test]\nvalue = 10\n");
-    ///
-    /// // change existing value
-    /// conf = conf.section("test").item("value", "updated");
-    /// assert_eq!(conf.to_string(), "[test]\nvalue = updated\n");
+    /// assert_eq!(conf.to_string_with(&FormatOptions::new().blank_lines(2)), "[a]\nb = 1\n\n\n[c]\nd = 2\n");
     /// ```
-    pub fn item<N, V>(mut self, name: N, value: V) -> Self
+    pub fn to_string_with(&self, options: &FormatOptions) -> String {
+        Formatted(self, options).to_string()
+    }
+
+    /// Write document line by line to `out` with given output style
+    fn write_with<W>(&self, options: &FormatOptions, out: &mut W) -> fmt::Result
     where
-        N: Into<String>,
-        V: fmt::Display,
+        W: fmt::Write,
     {
-        self.document
-            .entry(self.last_section_name.clone())
-            .or_insert_with(Section::new)
-            .insert(name.into(), value.to_string());
-        self
+        let line_ending = options.line_ending.as_str();
+        let separator = options.separator();
+        let indent = " ".repeat(options.indent);
+        // keys of global section are placed before the first section header
+        let global = self.document.get(&self.global_section).map(|section| (&self.global_section, section));
+        let sections = self.document.iter().filter(|&(name, _)| *name != self.global_section);
+        // line break is written before the next line, so that the last one may be omitted
+        let mut pending = false;
+        macro_rules! write_line {
+            ($($arg:tt)*) => {{
+                if pending {
+                    out.write_str(line_ending)?;
+                }
+                if options.escape_non_ascii {
+                    out.write_str(&escape::escape_non_ascii(&format!($($arg)*)))?;
+                } else {
+                    write!(out, $($arg)*)?;
+                }
+                pending = true;
+            }};
+        }
+        for line in self.preamble.iter().flat_map(|text| text.split('\n')) {
+            write_line!("; {}", line);
+        }
+        for (name, section) in global.into_iter().chain(sections) {
+            // blank lines between sections and after preamble
+            if pending {
+                out.write_str(&line_ending.repeat(options.blank_lines))?;
+            }
+            let comments =
+                |key: Option<&String>| self.leading_comments.get(&(name.clone(), key.cloned())).into_iter().flatten();
+            // insert section block
+            for line in comments(None) {
+                write_line!("; {}", line);
+            }
+            if *name != self.global_section {
+                write_line!("[{}]", name);
+            }
+            // add items
+            // keys are padded to the longest one if values are aligned
+            let width = if options.align_values {
+                section.values.keys().map(|k| k.chars().count()).max().unwrap_or(0)
+            } else {
+                0
+            };
+            for (key, value) in &section.values {
+                for line in comments(Some(key)) {
+                    write_line!("{}; {}", indent, line);
+                }
+                let key = format!("{:width$}", key, width = width);
+                let value = escape::quote_with(value, options.quote_mode, options.delimiter);
+                // multi-line values are continued on lines indented deeper than key
+                let mut lines = value.split('\n');
+                let first = lines.next().unwrap_or_default();
+                match options.wrap_width {
+                    Some(wrap) => {
+                        let line = format!("{}{}{}", key, separator, first);
+                        for part in wrap_line(&line, key.len() + separator.len(), wrap.saturating_sub(indent.len())) {
+                            write_line!("{}{}", indent, part);
+                        }
+                    }
+                    None => write_line!("{}{}{}{}", indent, key, separator, first),
+                }
+                for line in lines {
+                    write_line!("{}    {}", indent, line);
+                }
+            }
+        }
+        if pending && options.trailing_newline {
+            out.write_str(line_ending)?;
+        }
+        Ok(())
     }
 
-    /// Like [`item()`](Ini::item), but for vectors
+    /// Set comment written at the start of document before all sections, replacing the previous one
     ///
-    /// - `name` must support [Into] to [String]
-    /// - `vector` elements must support [Display](fmt::Display) to support conversion to [String]
-    /// - `sep` arbitrary string delimiter
+    /// Comment lines at the start of parsed input, up to the first empty line, section or key, are
+    /// read as preamble, so it is replaced rather than repeated when the document is saved again.
+    /// Empty `text` removes preamble.
     ///
     /// # Example
     /// ```
     /// # use tini::Ini;
-    /// let conf = Ini::new()
-    ///     .section("default")
-    /// // add a vector with `,` separator: 1,2,3,4
-    ///     .item_vec_with_sep("a", &[1, 2, 3, 4], ",")
-    /// // add a vector with `|` separator: a|b|c
-    ///     .item_vec_with_sep("b", &vec!["a", "b", "c"], "|");
+    /// let conf = Ini::from_string("; generated by app v1\n\n[a]\nb = 1").unwrap();
+    /// assert_eq!(conf.get_preamble(), Some("generated by app v1"));
     ///
-    /// let va: Option<Vec<u8>> = conf.get_vec("default", "a");
-    /// let vb: Vec<String> = conf.get_vec_with_sep("default", "b", "|").unwrap();
-    ///
-    /// assert_eq!(va, Some(vec![1, 2, 3, 4]));
-    /// assert_eq!(vb, ["a", "b", "c"]);
+    /// let conf = conf.preamble("generated by app v2\ndo not edit");
+    /// assert_eq!(conf.to_string(), "; generated by app v2\n; do not edit\n\n[a]\nb = 1\n");
     /// ```
-    pub fn item_vec_with_sep<S, V>(mut self, name: S, vector: &[V], sep: &str) -> Self
+    pub fn preamble<S>(mut self, text: S) -> Self
     where
         S: Into<String>,
-        V: fmt::Display,
     {
-        let vector_data = vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(sep);
-        self.document
-            .entry(self.last_section_name.clone())
-            .or_insert_with(Section::new)
-            .insert(name.into(), vector_data);
+        let text = text.into();
+        self.preamble = if text.is_empty() { None } else { Some(text) };
+        self.modified = true;
         self
     }
 
-    /// Equivalent of [`item_vec_with_sep(name, vector, ", ")`](Ini::item_vec_with_sep)
+    /// Get comment at the start of document, see [`preamble()`](Ini::preamble)
+    pub fn get_preamble(&self) -> Option<&str> {
+        self.preamble.as_deref()
+    }
+
+    /// Sort sections in alphabetical order, instead of order of appearance
     ///
-    /// - `name` must support [Into] to [String]
-    /// - `vector` elements must support [Display](fmt::Display) to support conversion to [String]
+    /// Keys before the first section header are always written first.
     ///
     /// # Example
     /// ```
     /// # use tini::Ini;
-    /// let conf = Ini::new()
-    ///     .section("default")
-    /// // add vector with default separator `, `
-    ///     .item_vec("a", &[1, 2, 3, 4])
-    /// // and another vector
-    ///     .item_vec("b", &vec!["a", "b", "c"]);
-    ///
-    /// let va: Option<Vec<u8>> = conf.get_vec("default", "a");
-    /// let vb: Vec<String> = conf.get_vec("default", "b").unwrap();
+    /// let conf = Ini::from_string("[b]\nx = 1\n[a]\ny = 2").unwrap().sort_sections();
     ///
-    /// assert_eq!(va, Some(vec![1, 2, 3, 4]));
-    /// assert_eq!(vb, ["a", "b", "c"]);
+    /// assert_eq!(conf.to_string(), "[a]\ny = 2\n\n[b]\nx = 1\n");
     /// ```
-    pub fn item_vec<S, V>(self, name: S, vector: &[V]) -> Self
-    where
-        S: Into<String>,
-        V: fmt::Display,
-    {
-        self.item_vec_with_sep(name, vector, ", ")
+    pub fn sort_sections(mut self) -> Self {
+        self.document.sort_keys();
+        self.modified = true;
+        self
     }
 
-    /// Append pairs from any object supporting [IntoIterator] to the section, specified in last [`section()`](Ini::section) call.
+    /// Sort keys of every section in alphabetical order, instead of order of appearance
     ///
     /// # Example
     /// ```
     /// # use tini::Ini;
-    /// use std::collections::HashMap;
-    ///
-    /// let mut conf = Ini::new()
-    ///                .section("colors")
-    ///                .items(vec![("black", "#000000"),
-    ///                            ("white", "#ffffff")]);
-    ///
-    /// // create custom section
-    /// let mut numbers = HashMap::new();
-    /// numbers.insert("round_pi", 3);
-    /// // and add to `conf`
-    /// conf = conf.section("numbers").items(numbers);
+    /// let conf = Ini::from_string("[a]\nz = 1\ny = 2\nx = 3").unwrap().sort_keys();
     ///
-    /// assert_eq!(conf.to_string(), [
-    ///                               "[colors]",
-    ///                               "black = #000000",
-    ///                               "white = #ffffff",
-    ///                               "",
-    ///                               "[numbers]",
-    ///                               "round_pi = 3",
-    ///                               ""
-    ///                              ].join("\n"));
+    /// assert_eq!(conf.to_string(), "[a]\nx = 3\ny = 2\nz = 1\n");
     /// ```
-    pub fn items<K, V, I>(mut self, items: I) -> Self
-    where
-        K: Into<String>,
-        V: fmt::Display,
-        I: IntoIterator<Item = (K, V)>,
-    {
-        for (k, v) in items {
-            self = self.item(k.into(), v.to_string());
+    pub fn sort_keys(mut self) -> Self {
+        for (_, section) in self.document.iter_mut() {
+            section.values.sort_keys();
         }
+        self.modified = true;
         self
     }
 
-    /// Remove section from [Ini].
+    /// Set output style used by [Display](#impl-Display-for-Ini), [`to_writer()`](Ini::to_writer) and
+    /// [`to_file()`](Ini::to_file)
     ///
     /// # Example
     /// ```
-    /// # use tini::Ini;
-    /// let mut config = Ini::from_string([
-    ///                                    "[one]",
-    ///                                    "a = 1",
-    ///                                    "[two]",
-    ///                                    "b = 2"
-    ///                                   ].join("\n")).unwrap();
-    /// // remove section
-    /// config = config.section("one").clear();
-    /// assert_eq!(config.to_string(), "[two]\nb = 2\n");
+    /// # use tini::{FormatOptions, Ini};
+    /// let conf = Ini::new().format(FormatOptions::new().indent(4)).section("a").item("b", 1);
     ///
-    /// // clear section from old data and add new
-    /// config = config.section("two").clear().item("a", 1);
-    /// assert_eq!(config.to_string(), "[two]\na = 1\n");
+    /// assert_eq!(conf.to_string(), "[a]\n    b = 1\n");
     /// ```
-    pub fn clear(mut self) -> Self {
-        self.document.remove(&self.last_section_name);
+    pub fn format(mut self, options: FormatOptions) -> Self {
+        self.format = options;
         self
     }
 
-    /// Remove item from section.
+    /// Enable or disable UTF-8 byte order mark at the beginning of [`to_writer()`](Ini::to_writer) and
+    /// [`to_file()`](Ini::to_file) output. By default it's enabled only if parsed input starts with it.
     ///
     /// # Example
     /// ```
     /// # use tini::Ini;
-    /// let mut config = Ini::from_string([
-    ///                                    "[one]",
-    ///                                    "a = 1",
-    ///                                    "b = 2"
-    ///                                   ].join("\n")).unwrap();
+    /// let conf = Ini::from_string("\u{feff}[a]\nb = 1").unwrap();
     ///
-    /// config = config.section("one").erase("b");
+    /// let b: Option<u8> = conf.get("a", "b");
+    /// assert_eq!(b, Some(1));
     ///
-    /// assert_eq!(config.to_string(), "[one]\na = 1\n");
+    /// let mut output = Vec::new();
+    /// conf.bom(false).to_writer(&mut output).unwrap();
+    /// assert_eq!(output, b"[a]\nb = 1\n");
     /// ```
-    pub fn erase(mut self, key: &str) -> Self {
-        self.document.get_mut(&self.last_section_name).and_then(|s| s.remove(key));
+    pub fn bom(mut self, enabled: bool) -> Self {
+        self.bom = enabled;
         self
     }
 
-    /// Private method which get value by `key` from `section`
-    fn get_raw(&self, section: &str, key: &str) -> Option<&String> {
-        self.document.get(section).and_then(|s| s.get(key))
-    }
-
-    /// Get scalar value of key in section.
-    ///
-    /// - output type `T` must implement [FromStr] trait for auto conversion
+    /// Use section `name` as fallback for keys which are missing in other sections, like `[DEFAULT]` in Python's
+    /// configparser. Fallback applies to [`get_raw()`](Ini::get_raw), all typed getters and
+    /// [`get_section_as()`](Ini::get_section_as), but not to [Section] views; also see
+    /// [`ParserConfig::default_section()`](ParserConfig::default_section).
     ///
     /// # Example
     /// ```
     /// # use tini::Ini;
-    /// let conf = Ini::from_string("[section]\none = 1").unwrap();
-    ///
-    /// let value: Option<u8> = conf.get("section", "one");
+    /// let conf = Ini::from_string("[DEFAULT]\ntimeout = 30\n[server]\nhost = example.com\n[client]\ntimeout = 5")
+    ///     .unwrap()
+    ///     .default_section("DEFAULT");
     ///
-    /// assert_eq!(value, Some(1));
+    /// assert_eq!(conf.get::<u32>("server", "timeout"), Some(30));
+    /// assert_eq!(conf.get::<u32>("client", "timeout"), Some(5));
+    /// assert_eq!(conf.get::<u32>("missing", "timeout"), None);
     /// ```
-    pub fn get<T>(&self, section: &str, key: &str) -> Option<T>
+    pub fn default_section<S>(mut self, name: S) -> Self
     where
-        T: FromStr,
+        S: Into<String>,
     {
-        self.get_raw(section, key).and_then(|x| x.parse().ok())
+        let name = name.into();
+        self.default_section = Some(self.normalize(&name).into_owned());
+        self
     }
 
-    /// Get vector value of `key` in `section`. Value should use `,` as separator.
+    /// Wrap long `key = value` lines on output to fit into `width` columns, using backslash line continuation.
     ///
-    /// The function returns [None](Option::None) if one of the elements can not be parsed.
-    ///
-    /// - output type `T` must implement [FromStr] trait for auto conversion
+    /// Lines are broken on spaces only, so a value without spaces is never wrapped.
+    /// Such output should be read with [`line_continuation`](ParserConfig::line_continuation) enabled.
     ///
     /// # Example
     /// ```
     /// # use tini::Ini;
-    /// let conf = Ini::from_string("[section]\nlist = 1, 2, 3, 4").unwrap();
-    ///
-    /// let value: Option<Vec<u8>> = conf.get_vec("section", "list");
+    /// let conf = Ini::new().wrap_values(16).section("a").item("b", "one two three four");
+    /// assert_eq!(conf.to_string(), "[a]\nb = one two \\\n    three four\n");
     ///
-    /// assert_eq!(value, Some(vec![1, 2, 3, 4]));
+    /// let parsed = Ini::builder().line_continuation(true).parse_str(&conf.to_string()).unwrap();
+    /// let b: Option<String> = parsed.get("a", "b");
+    /// assert_eq!(b, Some("one two three four".to_owned()));
     /// ```
-    pub fn get_vec<T>(&self, section: &str, key: &str) -> Option<Vec<T>>
-    where
-        T: FromStr,
+    pub fn wrap_values(mut self, width: usize) -> Self {
+        self.format.wrap_width = Some(width);
+        self
+    }
+
+    /// Set quoting of values on output
+    ///
+    /// Use [`quoted_values`](ParserConfig::quoted_values) to read quoted values back.
+    /// With [QuoteMode::Auto] only values which would not be parsed back as is are quoted.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, QuoteMode};
+    /// let conf = Ini::new().quoting(QuoteMode::Always).section("a").item("b", "say \"hi\"\n");
+    /// assert_eq!(conf.to_string(), "[a]\nb = \"say \\\"hi\\\"\\n\"\n");
+    ///
+    /// let parsed = Ini::builder().quoted_values(true).parse_str(&conf.to_string()).unwrap();
+    /// let b: Option<String> = parsed.get("a", "b");
+    /// assert_eq!(b, Some("say \"hi\"\n".to_owned()));
+    ///
+    /// let conf = Ini::new().quoting(QuoteMode::Auto).section("a").item("b", "plain").item("c", " ; padded");
+    /// assert_eq!(conf.to_string(), "[a]\nb = plain\nc = \" ; padded\"\n");
+    /// ```
+    pub fn quoting(mut self, mode: QuoteMode) -> Self {
+        self.format.quote_mode = mode;
+        self
+    }
+
+    /// Set key-value delimiter on output (`=` by default)
+    ///
+    /// Delimiter `=` is surrounded by spaces, other delimiters are followed by space.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::new().delimiter(':').section("a").item("b", 1);
+    ///
+    /// assert_eq!(conf.to_string(), "[a]\nb: 1\n");
+    /// ```
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.format.delimiter = delimiter;
+        self
+    }
+
+    /// Set line break style on output
+    ///
+    /// By default, style of the first line break of parsed input is used, or [LineEnding::Lf] for new document.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, LineEnding};
+    /// let conf = Ini::from_string("[a]\r\nb = 1\r\n").unwrap();
+    /// assert_eq!(conf.to_string(), "[a]\r\nb = 1\r\n");
+    ///
+    /// let conf = conf.line_ending(LineEnding::Lf);
+    /// assert_eq!(conf.to_string(), "[a]\nb = 1\n");
+    /// ```
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.format.line_ending = line_ending;
+        self
+    }
+
+    /// Set section name for the following methods in chain ([`item()`](Ini::item), [`items()`](Ini::items), etc.)
+    ///
+    /// # Warning
+    /// This function doesn't create a section.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::new().section("empty");
+    /// assert_eq!(conf.to_string(), "");
+    ///
+    /// // but section will be created on item() call
+    /// conf = conf.section("one").item("a", 1);
+    /// assert_eq!(conf.to_string(), "[one]\na = 1\n");
+    /// ```
+    pub fn section<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.last_section_name = name.into();
+        if self.case_insensitive {
+            self.last_section_name = self.last_section_name.to_lowercase();
+        }
+        self.last_key = None;
+        self
+    }
+
+    /// Attach comment to the last section or key in chain, comment is written on the lines above it
+    ///
+    /// Comment after [`section()`](Ini::section) call belongs to section header, comment after
+    /// [`item()`](Ini::item) call belongs to the added key. Multiple comments and line breaks in comment
+    /// produce several comment lines.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::new()
+    ///     .section("db")
+    ///     .comment("primary database")
+    ///     .item("host", "localhost")
+    ///     .item("port", 5432)
+    ///     .comment("default port\nof postgres");
+    ///
+    /// assert_eq!(conf.to_string(), "; primary database\n[db]\nhost = localhost\n; default port\n; of postgres\nport = 5432\n");
+    /// ```
+    pub fn comment<S>(mut self, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.modified = true;
+        let target = (self.last_section_name.clone(), self.last_key.clone());
+        self.leading_comments.entry(target).or_default().extend(text.into().split('\n').map(String::from));
+        self
+    }
+
+    /// Like [`section()`](Ini::section), but checks that section header can be parsed back
+    ///
+    /// Name must not be empty, have leading or trailing whitespace, contain brackets,
+    /// line breaks or comment characters `;` and `#`.
+    ///
+    /// # Errors
+    /// This function will return [ValidationError::InvalidSection] for invalid name
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// assert!(Ini::new().try_section("server").is_ok());
+    /// assert!(Ini::new().try_section("server] ; [client").is_err());
+    /// ```
+    pub fn try_section<S>(self, name: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        if !is_valid_name(&name, &['[', ']']) {
+            return Err(ValidationError::InvalidSection(name).into());
+        }
+        Ok(self.section(name))
+    }
+
+    /// Like [`section()`](Ini::section), but for git-style section `[section "subsection"]`
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::new().section_sub("remote", "origin").item("url", "https://example.com/repo.git");
+    ///
+    /// assert_eq!(conf.to_string(), "[remote \"origin\"]\nurl = https://example.com/repo.git\n");
+    /// ```
+    pub fn section_sub(self, section: &str, subsection: &str) -> Self {
+        self.section(join_subsection(section, subsection))
+    }
+
+    /// Add key-value pair to the end of section, specified in last [`section()`](Ini::section) call,
+    /// or replace value if key already in section
+    ///
+    /// - `name` must support [Into] to [String]
+    /// - `value` must implement [ToIniValue] trait for conversion to [String]
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::new().section("test")
+    ///                      .item("value", 10);
+    ///
+    /// assert_eq!(conf.to_string(), "[test]\nvalue = 10\n");
+    ///
+    /// // change existing value
+    /// conf = conf.section("test").item("value", "updated");
+    /// assert_eq!(conf.to_string(), "[test]\nvalue = updated\n");
+    /// ```
+    pub fn item<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: ToIniValue,
+    {
+        let name = self.normalize(&name.into()).into_owned();
+        let section = self.last_section_name.clone();
+        self.set(&section, &name, value);
+        self.last_key = Some(name);
+        self
+    }
+
+    /// Like [`item()`](Ini::item), but checks that key-value pair can be parsed back
+    ///
+    /// Key must not be empty, have leading or trailing whitespace, start with `[`, contain delimiter,
+    /// line breaks or comment characters `;` and `#`. Value must not have leading or trailing whitespace,
+    /// line breaks or comment characters unless values are written in quotes (see [`quoting()`](Ini::quoting)).
+    ///
+    /// # Errors
+    /// This function will return [ValidationError] for invalid key or value
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, QuoteMode};
+    /// let conf = Ini::new().section("a").try_item("b", "1").unwrap();
+    /// assert_eq!(conf.to_string(), "[a]\nb = 1\n");
+    ///
+    /// assert!(Ini::new().section("a").try_item("c = d", 1).is_err());
+    /// assert!(Ini::new().section("a").try_item("c", "two\nlines").is_err());
+    /// assert!(Ini::new().quoting(QuoteMode::Always).section("a").try_item("c", "two\nlines").is_ok());
+    /// ```
+    pub fn try_item<N, V>(self, name: N, value: V) -> Result<Self, Error>
+    where
+        N: Into<String>,
+        V: ToIniValue,
+    {
+        let (name, value) = (name.into(), value.format_value());
+        if !is_valid_name(&name, &['=', self.format.delimiter]) || name.starts_with('[') {
+            return Err(ValidationError::InvalidKey(name).into());
+        }
+        let quoted = self.format.quote_mode != QuoteMode::Never;
+        if !quoted && !value.is_empty() && !is_valid_name(&value, &[]) {
+            return Err(ValidationError::InvalidValue(name, value).into());
+        }
+        Ok(self.item(name, value))
+    }
+
+    /// Like [`item()`](Ini::item), but for vectors
+    ///
+    /// - `name` must support [Into] to [String]
+    /// - `vector` elements must implement [ToIniValue] trait for conversion to [String]
+    /// - `sep` arbitrary string delimiter
+    ///
+    /// Separator is remembered for the key and used by [`push_to_vec()`](Ini::push_to_vec)
+    /// and [`remove_from_vec()`](Ini::remove_from_vec).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::new()
+    ///     .section("default")
+    /// // add a vector with `,` separator: 1,2,3,4
+    ///     .item_vec_with_sep("a", &[1, 2, 3, 4], ",")
+    /// // add a vector with `|` separator: a|b|c
+    ///     .item_vec_with_sep("b", &vec!["a", "b", "c"], "|");
+    ///
+    /// let va: Option<Vec<u8>> = conf.get_vec("default", "a");
+    /// let vb: Vec<String> = conf.get_vec_with_sep("default", "b", "|").unwrap();
+    ///
+    /// assert_eq!(va, Some(vec![1, 2, 3, 4]));
+    /// assert_eq!(vb, ["a", "b", "c"]);
+    /// ```
+    pub fn item_vec_with_sep<S, V>(mut self, name: S, vector: &[V], sep: &str) -> Self
+    where
+        S: Into<String>,
+        V: ToIniValue,
+    {
+        let name = self.normalize(&name.into()).into_owned();
+        let section = self.last_section_name.clone();
+        self.set_vec_with_sep(&section, &name, vector, sep);
+        self.last_key = Some(name);
+        self
+    }
+
+    /// Equivalent of [`item_vec_with_sep(name, vector, ", ")`](Ini::item_vec_with_sep)
+    ///
+    /// - `name` must support [Into] to [String]
+    /// - `vector` elements must implement [ToIniValue] trait for conversion to [String]
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::new()
+    ///     .section("default")
+    /// // add vector with default separator `, `
+    ///     .item_vec("a", &[1, 2, 3, 4])
+    /// // and another vector
+    ///     .item_vec("b", &vec!["a", "b", "c"]);
+    ///
+    /// let va: Option<Vec<u8>> = conf.get_vec("default", "a");
+    /// let vb: Vec<String> = conf.get_vec("default", "b").unwrap();
+    ///
+    /// assert_eq!(va, Some(vec![1, 2, 3, 4]));
+    /// assert_eq!(vb, ["a", "b", "c"]);
+    /// ```
+    pub fn item_vec<S, V>(self, name: S, vector: &[V]) -> Self
+    where
+        S: Into<String>,
+        V: ToIniValue,
+    {
+        self.item_vec_with_sep(name, vector, ", ")
+    }
+
+    /// Append pairs from any object supporting [IntoIterator] to the section, specified in last [`section()`](Ini::section) call.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// use std::collections::{HashMap, HashSet};
+    ///
+    /// let mut conf = Ini::new()
+    ///                .section("colors")
+    ///                .items(vec![("black", "#000000"),
+    ///                            ("white", "#ffffff")]);
+    ///
+    /// // create custom section
+    /// let mut numbers = HashMap::new();
+    /// numbers.insert("round_pi", 3);
+    /// // and add to `conf`
+    /// conf = conf.section("numbers").items(numbers);
+    ///
+    /// assert_eq!(conf.to_string(), [
+    ///                               "[colors]",
+    ///                               "black = #000000",
+    ///                               "white = #ffffff",
+    ///                               "",
+    ///                               "[numbers]",
+    ///                               "round_pi = 3",
+    ///                               ""
+    ///                              ].join("\n"));
+    /// ```
+    pub fn items<K, V, I>(mut self, items: I) -> Self
+    where
+        K: Into<String>,
+        V: ToIniValue,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in items {
+            self = self.item(k.into(), v.format_value());
+        }
+        self
+    }
+
+    /// Remove section from [Ini].
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut config = Ini::from_string([
+    ///                                    "[one]",
+    ///                                    "a = 1",
+    ///                                    "[two]",
+    ///                                    "b = 2"
+    ///                                   ].join("\n")).unwrap();
+    /// // remove section
+    /// config = config.section("one").clear();
+    /// assert_eq!(config.to_string(), "[two]\nb = 2\n");
+    ///
+    /// // clear section from old data and add new
+    /// config = config.section("two").clear().item("a", 1);
+    /// assert_eq!(config.to_string(), "[two]\na = 1\n");
+    /// ```
+    pub fn clear(mut self) -> Self {
+        let name = self.last_section_name.clone();
+        self.remove_section(&name);
+        self
+    }
+
+    /// Remove section from [Ini] and return it, if it was present
+    ///
+    /// Unlike [`clear()`](Ini::clear) doesn't consume [Ini], so section can be inspected or moved elsewhere.
+    /// Comments of the section and its keys are removed too.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut config = Ini::from_string("[one]\na = 1\n[two]\nb = 2\n[three]\nc = 3").unwrap();
+    ///
+    /// let one = config.remove_section("one").unwrap();
+    /// assert_eq!(one.get::<u8>("a"), Some(1));
+    /// assert!(config.remove_section("one").is_none());
+    /// assert_eq!(config.to_string(), "[two]\nb = 2\n\n[three]\nc = 3\n");
+    /// ```
+    pub fn remove_section(&mut self, name: &str) -> Option<Section> {
+        let name = self.normalize(name).into_owned();
+        self.inline_comments.remove(&name);
+        self.section_comments.remove(&name);
+        self.list_separators.remove(&name);
+        self.leading_comments.retain(|(section, _), _| *section != name);
+        let section = self.document.remove(&name);
+        self.modified |= section.is_some();
+        section
+    }
+
+    /// Keep only sections for which `f` returns `true`, removing others like [`remove_section()`](Ini::remove_section)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nx = 1\n[b]\n[obsolete]\ny = 2").unwrap();
+    ///
+    /// conf.retain_sections(|name, section| !section.is_empty() && name != "obsolete");
+    /// assert_eq!(conf.to_string(), "[a]\nx = 1\n");
+    /// ```
+    pub fn retain_sections<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, &Section) -> bool,
+    {
+        let removed: Vec<String> =
+            self.document.iter().filter(|(name, section)| !f(name, section)).map(|(name, _)| name.clone()).collect();
+        for name in removed {
+            self.remove_section(&name);
+        }
+    }
+
+    /// Create new document with only `sections` of this one, keeping their order in document, comments and formatting options
+    ///
+    /// Unknown names are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[server]\nport = 80\n[secrets]\ntoken = xyz\n[logging]\nlevel = info").unwrap();
+    ///
+    /// let plugin = conf.select(&["logging", "server", "cache"]);
+    /// assert_eq!(plugin.to_string(), "[server]\nport = 80\n\n[logging]\nlevel = info\n");
+    /// ```
+    pub fn select<S>(&self, sections: &[S]) -> Ini
+    where
+        S: AsRef<str>,
+    {
+        let names: Vec<Cow<str>> = sections.iter().map(|name| self.normalize(name.as_ref())).collect();
+        let mut result = self.clone();
+        result.retain_sections(|name, _| names.iter().any(|selected| selected == name));
+        result.modified = self.modified;
+        result
+    }
+
+    /// Remove item from section.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut config = Ini::from_string([
+    ///                                    "[one]",
+    ///                                    "a = 1",
+    ///                                    "b = 2"
+    ///                                   ].join("\n")).unwrap();
+    ///
+    /// config = config.section("one").erase("b");
+    ///
+    /// assert_eq!(config.to_string(), "[one]\na = 1\n");
+    /// ```
+    pub fn erase(mut self, key: &str) -> Self {
+        let section = self.last_section_name.clone();
+        self.remove(&section, key);
+        self
+    }
+
+    /// Remove `key` from `section` and return its raw value, if it was present
+    ///
+    /// Unlike [`erase()`](Ini::erase) doesn't consume [Ini]. Comments of the key are removed too.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut config = Ini::from_string("[app]\nold_port = 8080\nhost = localhost").unwrap();
+    ///
+    /// // migrate legacy key
+    /// if let Some(port) = config.remove("app", "old_port") {
+    ///     config = config.section("app").item("port", port);
+    /// }
+    /// assert_eq!(config.remove("app", "old_port"), None);
+    /// assert_eq!(config.to_string(), "[app]\nhost = localhost\nport = 8080\n");
+    /// ```
+    pub fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        self.inline_comments.get_mut(&section).and_then(|s| s.remove(&key));
+        self.list_separators.get_mut(&section).and_then(|s| s.remove(&key));
+        let value = self.document.get_mut(&section).and_then(|s| s.values.remove(&key));
+        self.leading_comments.remove(&(section, Some(key)));
+        self.modified |= value.is_some();
+        value
+    }
+
+    /// Get separator of vector `key` in `section` added with [`item_vec_with_sep()`](Ini::item_vec_with_sep)
+    /// or [`item_vec()`](Ini::item_vec)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::new().section("a").item_vec_with_sep("list", &[1, 2], "|").item("b", 1);
+    ///
+    /// assert_eq!(conf.vec_separator("a", "list"), Some("|"));
+    /// assert_eq!(conf.vec_separator("a", "b"), None);
+    /// ```
+    pub fn vec_separator(&self, section: &str, key: &str) -> Option<&str> {
+        self.list_separators.get(&*self.normalize(section)).and_then(|s| s.get(&*self.normalize(key))).map(|s| &s[..])
+    }
+
+    /// Append element to the end of vector `key` in `section`
+    ///
+    /// Elements are joined with separator of [`vec_separator()`](Ini::vec_separator), or `, ` for keys which
+    /// were not added as vectors. Absent key is created with a single element.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::new().section("a").item_vec_with_sep("list", &[1, 2], "|");
+    /// conf.push_to_vec("a", "list", 3);
+    /// conf.push_to_vec("a", "new", "x");
+    /// conf.push_to_vec("a", "new", "y");
+    ///
+    /// assert_eq!(conf.to_string(), "[a]\nlist = 1|2|3\nnew = x, y\n");
+    /// ```
+    pub fn push_to_vec<V>(&mut self, section: &str, key: &str, value: V)
+    where
+        V: ToIniValue,
+    {
+        let sep = self.vec_separator(section, key).unwrap_or(", ").to_owned();
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        let values = self.section_mut(section);
+        match values.values.get_mut(&key) {
+            Some(list) if !list.is_empty() => {
+                list.push_str(&sep);
+                list.push_str(&value.format_value());
+            }
+            Some(list) => *list = value.format_value(),
+            None => {
+                values.values.insert(key, value.format_value());
+            }
+        }
+        self.modified = true;
+    }
+
+    /// Remove all elements equal to `value` from vector `key` in `section`, returns `true` if any element was removed
+    ///
+    /// Vector is split by separator of [`vec_separator()`](Ini::vec_separator) or `,` for keys which were
+    /// not added as vectors, remaining elements are joined back with the same separator.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nlist = 1, 2,3").unwrap();
+    ///
+    /// assert!(conf.remove_from_vec("a", "list", 2));
+    /// assert!(!conf.remove_from_vec("a", "list", 4));
+    /// assert_eq!(conf.to_string(), "[a]\nlist = 1, 3\n");
+    /// ```
+    pub fn remove_from_vec<V>(&mut self, section: &str, key: &str, value: V) -> bool
+    where
+        V: ToIniValue,
+    {
+        let sep = self.vec_separator(section, key).unwrap_or(", ").to_owned();
+        // split by separator without surrounding spaces to accept hand-written lists
+        let split = if sep.trim().is_empty() { &sep[..] } else { sep.trim() };
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        let list = match self.document.get_mut(&section).and_then(|s| s.values.get_mut(&key)) {
+            Some(list) => list,
+            None => return false,
+        };
+        let value = value.format_value();
+        let elements: Vec<&str> = list.split(split).map(str::trim).collect();
+        let retained: Vec<&str> = elements.iter().copied().filter(|e| *e != value).collect();
+        if retained.len() == elements.len() {
+            return false;
+        }
+        *list = retained.join(&sep);
+        self.modified = true;
+        true
+    }
+
+    /// Set `value` of `key` in `section`, section and key are added to the end if they are absent
+    ///
+    /// Unlike [`item()`](Ini::item) doesn't consume [Ini], which is convenient when it is stored in a struct field.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// struct App {
+    ///     config: Ini,
+    /// }
+    ///
+    /// let mut app = App { config: Ini::new() };
+    /// app.config.set("window", "width", 800);
+    /// app.config.set("window", "height", 600);
+    /// app.config.set("window", "width", 1024);
+    ///
+    /// assert_eq!(app.config.to_string(), "[window]\nwidth = 1024\nheight = 600\n");
+    /// ```
+    pub fn set<V>(&mut self, section: &str, key: &str, value: V)
+    where
+        V: ToIniValue,
+    {
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        self.list_separators.get_mut(&section).and_then(|s| s.remove(&key));
+        self.section_mut(section).values.insert(key, value.format_value());
+        self.modified = true;
+    }
+
+    /// Set value by `path` of section and key joined with `.`, see [`get_path()`](Ini::get_path)
+    ///
+    /// Returns `false` and leaves document unchanged if `path` has no `.`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::new();
+    ///
+    /// // e.g. value of `--set server.port=8080` command line option
+    /// let (path, value) = "server.port=8080".split_once('=').unwrap();
+    /// assert!(conf.set_path(path, value));
+    /// assert!(!conf.set_path("port", 80));
+    /// assert_eq!(conf.to_string(), "[server]\nport = 8080\n");
+    /// ```
+    pub fn set_path<V>(&mut self, path: &str, value: V) -> bool
+    where
+        V: ToIniValue,
+    {
+        match path.rsplit_once('.') {
+            Some((section, key)) => {
+                self.set(section, key, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get all values as pairs of path `section.key` and value in order of appearance
+    ///
+    /// Keys before the first section header (see [`global_section()`](ParserConfig::global_section))
+    /// are yielded without section prefix. Pairs can be collected into any map, see [`from_flat()`](Ini::from_flat)
+    /// for the inverse conversion.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[server]\nhost = localhost\nport = 8080\n[log]\nlevel = info").unwrap();
+    ///
+    /// let env: Vec<String> = conf.flatten().iter().map(|(path, value)| format!("{}={}", path, value)).collect();
+    /// assert_eq!(env, ["server.host=localhost", "server.port=8080", "log.level=info"]);
+    /// ```
+    pub fn flatten(&self) -> Vec<(String, String)> {
+        let mut result = Vec::new();
+        for (name, section) in self.document.iter() {
+            for (key, value) in section.values.iter() {
+                let path = if *name == self.global_section { key.clone() } else { format!("{}.{}", name, key) };
+                result.push((path, value.clone()));
+            }
+        }
+        result
+    }
+
+    /// Build document from pairs of path `section.key` and value, see [`flatten()`](Ini::flatten)
+    ///
+    /// Path is split by the last `.` like in [`get_path()`](Ini::get_path), paths without `.` or with empty
+    /// section name like `.key` are keys before the first section header.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_flat(vec![("name", "app"), ("server.port", "8080"), ("log.file.path", "app.log")]);
+    ///
+    /// assert_eq!(conf.to_string(), "name = app\n\n[server]\nport = 8080\n\n[log.file]\npath = app.log\n");
+    /// assert_eq!(Ini::from_flat(conf.flatten()), conf);
+    /// ```
+    pub fn from_flat<I, K, V>(pairs: I) -> Ini
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: ToIniValue,
+    {
+        let mut result = Ini::new();
+        for (path, value) in pairs {
+            let (section, key) = path.as_ref().rsplit_once('.').unwrap_or(("", path.as_ref()));
+            result.set(section, key, value);
+        }
+        result
+    }
+
+    /// Set `value` of `key` in `section` placing key at position `index` in section
+    ///
+    /// Key is placed at the end if `index` is out of bounds, present key is moved to `index`.
+    /// Section is added to the end if it is absent.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[db]\nhost = localhost\nuser = admin").unwrap();
+    /// conf.insert_at("db", 1, "port", 5432);
+    ///
+    /// assert_eq!(conf.to_string(), "[db]\nhost = localhost\nport = 5432\nuser = admin\n");
+    /// ```
+    pub fn insert_at<V>(&mut self, section: &str, index: usize, key: &str, value: V)
+    where
+        V: ToIniValue,
+    {
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        self.list_separators.get_mut(&section).and_then(|s| s.remove(&key));
+        self.section_mut(section).values.insert_at(index, key, value.format_value());
+        self.modified = true;
+    }
+
+    /// Like [`set()`](Ini::set), but for vectors joined with `sep`, see [`item_vec_with_sep()`](Ini::item_vec_with_sep)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::new();
+    /// conf.set_vec_with_sep("a", "list", &[1, 2, 3], "|");
+    /// assert_eq!(conf.to_string(), "[a]\nlist = 1|2|3\n");
+    ///
+    /// // separator is kept for incremental updates
+    /// conf.push_to_vec("a", "list", 4);
+    /// conf.remove_from_vec("a", "list", 1);
+    /// assert_eq!(conf.to_string(), "[a]\nlist = 2|3|4\n");
+    /// ```
+    pub fn set_vec_with_sep<V>(&mut self, section: &str, key: &str, vector: &[V], sep: &str)
+    where
+        V: ToIniValue,
+    {
+        let vector_data = vector.iter().map(|v| v.format_value()).collect::<Vec<_>>().join(sep);
+        self.set(section, key, vector_data);
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        self.list_separators.entry(section).or_default().insert(key, sep.to_owned());
+    }
+
+    /// Equivalent of [`set_vec_with_sep(section, key, vector, ", ")`](Ini::set_vec_with_sep)
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::new();
+    /// conf.set_vec("a", "list", &["x", "y"]);
+    ///
+    /// assert_eq!(conf.to_string(), "[a]\nlist = x, y\n");
+    /// ```
+    pub fn set_vec<V>(&mut self, section: &str, key: &str, vector: &[V])
+    where
+        V: ToIniValue,
+    {
+        self.set_vec_with_sep(section, key, vector, ", ")
+    }
+
+    /// Remove `key` from `section`, returns `true` if key was present
+    ///
+    /// Use [`remove()`](Ini::remove) to get the removed value.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nb = 1\nc = 2").unwrap();
+    ///
+    /// assert!(conf.delete_key("a", "b"));
+    /// assert!(!conf.delete_key("a", "b"));
+    /// assert_eq!(conf.to_string(), "[a]\nc = 2\n");
+    /// ```
+    pub fn delete_key(&mut self, section: &str, key: &str) -> bool {
+        self.remove(section, key).is_some()
+    }
+
+    /// Remove `section`, returns `true` if section was present
+    ///
+    /// Use [`remove_section()`](Ini::remove_section) to get the removed section.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nb = 1\n[c]\nd = 2").unwrap();
+    ///
+    /// assert!(conf.delete_section("a"));
+    /// assert!(!conf.delete_section("a"));
+    /// assert_eq!(conf.to_string(), "[c]\nd = 2\n");
+    /// ```
+    pub fn delete_section(&mut self, section: &str) -> bool {
+        self.remove_section(section).is_some()
+    }
+
+    /// Rename section `old` to `new` keeping its position, keys and comments
+    ///
+    /// Returns `false` and leaves document unchanged if `old` is absent or `new` is already present.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nx = 1\n[old]\ny = 2\n[b]\nz = 3").unwrap();
+    ///
+    /// assert!(conf.rename_section("old", "new"));
+    /// assert!(!conf.rename_section("new", "a"));
+    /// assert_eq!(conf.to_string(), "[a]\nx = 1\n\n[new]\ny = 2\n\n[b]\nz = 3\n");
+    /// ```
+    pub fn rename_section(&mut self, old: &str, new: &str) -> bool {
+        let (old, new) = (self.normalize(old).into_owned(), self.normalize(new).into_owned());
+        if !self.document.rename_key(&old, new.clone()) {
+            return false;
+        }
+        if let Some(comments) = self.inline_comments.remove(&old) {
+            self.inline_comments.insert(new.clone(), comments);
+        }
+        if let Some(comment) = self.section_comments.remove(&old) {
+            self.section_comments.insert(new.clone(), comment);
+        }
+        if let Some(separators) = self.list_separators.remove(&old) {
+            self.list_separators.insert(new.clone(), separators);
+        }
+        let moved: Vec<_> = self.leading_comments.keys().filter(|(section, _)| *section == old).cloned().collect();
+        for key in moved {
+            if let Some(comments) = self.leading_comments.remove(&key) {
+                self.leading_comments.insert((new.clone(), key.1), comments);
+            }
+        }
+        if self.last_section_name == old {
+            self.last_section_name = new;
+        }
+        self.modified = true;
+        true
+    }
+
+    /// Move section `name` to position `index` among sections, keeping its keys and comments
+    ///
+    /// Section is moved to the end if `index` is out of bounds. Returns `false` if section is absent.
+    /// Keys before the first section header are always written first.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nx = 1\n[b]\ny = 2\n[main]\nz = 3").unwrap();
+    ///
+    /// assert!(conf.move_section("main", 0));
+    /// assert!(!conf.move_section("other", 0));
+    /// assert_eq!(conf.to_string(), "[main]\nz = 3\n\n[a]\nx = 1\n\n[b]\ny = 2\n");
+    /// ```
+    pub fn move_section(&mut self, name: &str, index: usize) -> bool {
+        let name = self.normalize(name).into_owned();
+        let moved = self.document.move_key(&name, index);
+        self.modified |= moved;
+        moved
+    }
+
+    /// Move section `name` right before section `other`
+    ///
+    /// Returns `false` and leaves document unchanged if any of sections is absent.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nx = 1\n[b]\ny = 2\n[main]\nz = 3").unwrap();
+    ///
+    /// assert!(conf.move_section_before("main", "b"));
+    /// assert_eq!(conf.to_string(), "[a]\nx = 1\n\n[main]\nz = 3\n\n[b]\ny = 2\n");
+    /// ```
+    pub fn move_section_before(&mut self, name: &str, other: &str) -> bool {
+        let (name, other) = (self.normalize(name).into_owned(), self.normalize(other).into_owned());
+        match (self.document.position(&name), self.document.position(&other)) {
+            // position of `other` shifts left when `name` is taken from before it
+            (Some(from), Some(to)) => self.move_section(&name, if from < to { to - 1 } else { to }),
+            _ => false,
+        }
+    }
+
+    /// Move `key` of `section` to position `index` in section, keeping its value and comments
+    ///
+    /// Key is moved to the end if `index` is out of bounds. Returns `false` if section or key is absent.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[db]\nuser = admin\nhost = localhost\nport = 5432").unwrap();
+    ///
+    /// assert!(conf.move_key("db", "user", 10));
+    /// assert!(!conf.move_key("db", "password", 0));
+    /// assert_eq!(conf.to_string(), "[db]\nhost = localhost\nport = 5432\nuser = admin\n");
+    /// ```
+    pub fn move_key(&mut self, section: &str, key: &str, index: usize) -> bool {
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        let moved = self.document.get_mut(&section).is_some_and(|s| s.values.move_key(&key, index));
+        self.modified |= moved;
+        moved
+    }
+
+    /// Move `key` of `section` right before key `other` of the same section
+    ///
+    /// Returns `false` and leaves document unchanged if section or any of keys is absent.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[db]\nhost = localhost\nuser = admin\nport = 5432").unwrap();
+    ///
+    /// assert!(conf.move_key_before("db", "port", "user"));
+    /// assert_eq!(conf.to_string(), "[db]\nhost = localhost\nport = 5432\nuser = admin\n");
+    /// ```
+    pub fn move_key_before(&mut self, section: &str, key: &str, other: &str) -> bool {
+        let (key, other) = (self.normalize(key).into_owned(), self.normalize(other).into_owned());
+        let positions = self.get_section(section).map(|s| (s.values.position(&key), s.values.position(&other)));
+        match positions {
+            // position of `other` shifts left when `key` is taken from before it
+            Some((Some(from), Some(to))) => self.move_key(section, &key, if from < to { to - 1 } else { to }),
+            _ => false,
+        }
+    }
+
+    /// Rename key `old` to `new` in `section` keeping its position, value and comments
+    ///
+    /// Returns `false` and leaves document unchanged if `old` is absent or `new` is already present.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[app]\nhost = localhost\nold_port = 80\nname = test").unwrap();
+    ///
+    /// assert!(conf.rename_key("app", "old_port", "port"));
+    /// assert!(!conf.rename_key("app", "port", "host"));
+    /// assert_eq!(conf.to_string(), "[app]\nhost = localhost\nport = 80\nname = test\n");
+    /// ```
+    pub fn rename_key(&mut self, section: &str, old: &str, new: &str) -> bool {
+        let section = self.normalize(section).into_owned();
+        let (old, new) = (self.normalize(old).into_owned(), self.normalize(new).into_owned());
+        let renamed = self.document.get_mut(&section).map(|s| s.values.rename_key(&old, new.clone()));
+        if renamed != Some(true) {
+            return false;
+        }
+        for map in [&mut self.inline_comments, &mut self.list_separators] {
+            if let Some(keys) = map.get_mut(&section) {
+                if let Some(value) = keys.remove(&old) {
+                    keys.insert(new.clone(), value);
+                }
+            }
+        }
+        if let Some(comments) = self.leading_comments.remove(&(section.clone(), Some(old.clone()))) {
+            self.leading_comments.insert((section.clone(), Some(new.clone())), comments);
+        }
+        if self.last_section_name == section && self.last_key.as_deref() == Some(old.as_str()) {
+            self.last_key = Some(new);
+        }
+        self.modified = true;
+        true
+    }
+
+    /// Add sections and keys of `other` to this document, e.g. to apply user settings over system ones
+    ///
+    /// New sections are added to the end of document and new keys are added to the end of section
+    /// in order of `other`. Keys present in both documents keep their position and `policy` selects the value.
+    /// Comments of added keys and sections are taken from `other`.
+    ///
+    /// # Errors
+    /// This function will return [MergeError::Conflict] if key has different values in both documents
+    /// and `policy` is [MergePolicy::Error]
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, MergePolicy};
+    /// let system = Ini::from_string("[ui]\ntheme = light\nfont = mono\n[net]\nproxy = none").unwrap();
+    /// let user = Ini::from_string("[ui]\ntheme = dark\nsize = 12\n[keys]\nquit = q").unwrap();
+    ///
+    /// let conf = system.merge(user, MergePolicy::OverwriteExisting).unwrap();
+    /// assert_eq!(conf.to_string(), "[ui]\ntheme = dark\nfont = mono\nsize = 12\n\n[net]\nproxy = none\n\n[keys]\nquit = q\n");
+    ///
+    /// let other = Ini::from_string("[ui]\ntheme = blue").unwrap();
+    /// assert!(conf.merge(other, MergePolicy::Error).is_err());
+    /// ```
+    pub fn merge(mut self, other: Ini, policy: MergePolicy) -> Result<Ini, Error> {
+        self.merge_into(other, policy)?;
+        Ok(self)
+    }
+
+    /// Private method which merges `other` into this document, see [`merge()`](Ini::merge)
+    fn merge_into(&mut self, other: Ini, policy: MergePolicy) -> Result<(), MergeError> {
+        let Ini { document, mut inline_comments, section_comments, mut leading_comments, mut list_separators, .. } =
+            other;
+        let modified = self.modified;
+        let mut changed = false;
+        for (name, section) in document {
+            let name = self.normalize(&name).into_owned();
+            if !self.document.contains_key(&name) {
+                changed = true;
+                if let Some(comment) = section_comments.get(&name) {
+                    self.section_comments.insert(name.clone(), comment.clone());
+                }
+                if let Some(comments) = leading_comments.remove(&(name.clone(), None)) {
+                    self.leading_comments.insert((name.clone(), None), comments);
+                }
+            }
+            self.section_mut(name.clone());
+            for (key, value) in section.values {
+                let key = self.normalize(&key).into_owned();
+                let existing = self.document.get(&name).and_then(|s| s.values.get(&key));
+                match (existing, policy) {
+                    (None, _) | (Some(_), MergePolicy::OverwriteExisting) => (),
+                    (Some(old), MergePolicy::Error) if *old != value => {
+                        self.modified = modified || changed;
+                        return Err(MergeError::Conflict(name, key));
+                    }
+                    (Some(_), _) => continue,
+                }
+                changed |= existing != Some(&value);
+                let target = (name.clone(), Some(key.clone()));
+                match leading_comments.remove(&target) {
+                    Some(comments) => self.leading_comments.insert(target, comments),
+                    None => self.leading_comments.remove(&target),
+                };
+                let comment = inline_comments.get_mut(&name).and_then(|c| c.remove(&key));
+                let comments = self.inline_comments.entry(name.clone()).or_default();
+                match comment {
+                    Some(comment) => comments.insert(key.clone(), comment),
+                    None => comments.remove(&key),
+                };
+                let separator = list_separators.get_mut(&name).and_then(|s| s.remove(&key));
+                self.set(&name, &key, value);
+                if let Some(separator) = separator {
+                    self.list_separators.entry(name.clone()).or_default().insert(key, separator);
+                }
+            }
+        }
+        self.modified = modified || changed;
+        Ok(())
+    }
+
+    /// Add every section of `other` under name `prefix.section`, e.g. to compose config fragments of modules
+    ///
+    /// Keys before the first section header of `other` (see [`global_section()`](ParserConfig::global_section))
+    /// are added to section `prefix`. Sections and keys
+    /// which are already present are merged like [`merge()`](Ini::merge) with [MergePolicy::OverwriteExisting].
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let db = Ini::from_string("[pool]\nsize = 10 ; connections\n[log]\nlevel = warn").unwrap();
+    /// let conf = Ini::from_string("[app]\nname = test").unwrap().append_namespaced(db, "db");
+    ///
+    /// assert_eq!(conf.to_string(), "[app]\nname = test\n\n[db.pool]\nsize = 10\n\n[db.log]\nlevel = warn\n");
+    /// assert_eq!(conf.get_path::<u8>("db.pool.size"), Some(10));
+    /// assert_eq!(conf.inline_comment("db.pool", "size"), Some("connections"));
+    /// ```
+    pub fn append_namespaced(mut self, other: Ini, prefix: &str) -> Ini {
+        let Ini {
+            document, inline_comments, section_comments, leading_comments, list_separators, global_section, ..
+        } = other;
+        let target = |name: &str| {
+            let target = if name == global_section { prefix.to_owned() } else { format!("{}.{}", prefix, name) };
+            self.normalize(&target).into_owned()
+        };
+        let mut namespaced = Ini { case_insensitive: self.case_insensitive, ..Ini::new() };
+        for (name, section) in document {
+            namespaced.document.insert(target(&name), section);
+        }
+        namespaced.inline_comments = inline_comments.into_iter().map(|(name, c)| (target(&name), c)).collect();
+        namespaced.section_comments = section_comments.into_iter().map(|(name, c)| (target(&name), c)).collect();
+        namespaced.list_separators = list_separators.into_iter().map(|(name, s)| (target(&name), s)).collect();
+        namespaced.leading_comments =
+            leading_comments.into_iter().map(|((name, key), c)| ((target(&name), key), c)).collect();
+        // overwriting merge never reports conflicts
+        let _ = self.merge_into(namespaced, MergePolicy::OverwriteExisting);
+        self
+    }
+
+    /// Find sections and keys which were added, removed or changed in `other` compared to this document
+    ///
+    /// Comments and formatting are not compared, see [IniDiff] for details.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let old = Ini::from_string("[app]\nversion = 1\nlegacy = yes").unwrap();
+    /// let new = Ini::from_string("[app]\nversion = 2 ; upgraded").unwrap();
+    ///
+    /// assert_eq!(old.diff(&new).to_string(), "~ [app] version = 1 -> 2\n- [app] legacy = yes");
+    /// assert!(new.diff(&new).is_empty());
+    /// ```
+    pub fn diff(&self, other: &Ini) -> IniDiff {
+        IniDiff::new(self, other)
+    }
+
+    /// Check if documents have the same sections, keys and values in the same order
+    ///
+    /// Comments and output style are not compared.
+    pub fn eq_ordered(&self, other: &Ini) -> bool {
+        self.document.keys().len() == other.document.keys().len()
+            && self.document.iter().zip(other.document.iter()).all(|((a, x), (b, y))| a == b && x.eq_ordered(y))
+    }
+
+    /// Get entry of `section` for in-place manipulation
+    ///
+    /// Document is considered changed after this call, see [`is_modified()`](Ini::is_modified).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[stats]\nruns = 1").unwrap();
+    ///
+    /// for _ in 0..2 {
+    ///     let runs = conf.entry("stats").or_default().entry("runs").or_insert(0);
+    ///     *runs = (runs.parse::<u32>().unwrap() + 1).to_string();
+    /// }
+    /// conf.entry("paths").or_default().entry("home").or_insert("/home/user").push_str("/app");
+    ///
+    /// assert_eq!(conf.to_string(), "[stats]\nruns = 3\n\n[paths]\nhome = /home/user/app\n");
+    /// ```
+    pub fn entry(&mut self, section: &str) -> SectionEntry<'_> {
+        let name = self.normalize(section).into_owned();
+        self.modified = true;
+        SectionEntry { ini: self, name }
+    }
+
+    /// Private method which gets section by normalized `name`, adding it to the end of document if it is absent
+    pub(crate) fn section_mut(&mut self, name: String) -> &mut Section {
+        let case_insensitive = self.case_insensitive;
+        self.document.entry(name).or_insert_with(|| Section::with_case(case_insensitive))
+    }
+
+    /// Private method which converts name of section or key to lower case for case-insensitive document
+    fn normalize<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if self.case_insensitive {
+            Cow::Owned(name.to_lowercase())
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+
+    /// Get value of `key` in `section` as is, without conversion
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\npath = /usr/bin:/bin").unwrap();
+    ///
+    /// assert_eq!(conf.get_raw("section", "path"), Some("/usr/bin:/bin"));
+    /// assert_eq!(conf.get_raw("section", "other"), None);
+    /// ```
+    pub fn get_raw(&self, section: &str, key: &str) -> Option<&str> {
+        self.lookup(section, key).ok().flatten()
+    }
+
+    /// Private method which finds raw value of `key` in existing `section` falling back to
+    /// [`default_section()`](Ini::default_section), fails only if section is absent
+    fn lookup(&self, section: &str, key: &str) -> Result<Option<&str>, GetError> {
+        let found = self.get_section(section).ok_or(GetError::MissingSection)?;
+        Ok(found.get_raw(key).or_else(|| self.defaults().and_then(|defaults| defaults.get_raw(key))))
+    }
+
+    /// Private method which returns section set with [`default_section()`](Ini::default_section) if it's present
+    fn defaults(&self) -> Option<&Section> {
+        self.default_section.as_deref().and_then(|name| self.document.get(name))
+    }
+
+    /// Get `section` to read it independently of document
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, Section};
+    /// fn address(section: &Section) -> String {
+    ///     format!("{}:{}", section.get_raw("host").unwrap_or("localhost"), section.get_int::<u16>("port").unwrap_or(80))
+    /// }
+    ///
+    /// let conf = Ini::from_string("[http]\nport = 8080\n[ftp]\nhost = example.com\nport = 21").unwrap();
+    ///
+    /// assert_eq!(address(conf.get_section("http").unwrap()), "localhost:8080");
+    /// assert_eq!(address(conf.get_section("ftp").unwrap()), "example.com:21");
+    /// assert!(conf.get_section("ssh").is_none());
+    /// ```
+    pub fn get_section(&self, section: &str) -> Option<&Section> {
+        self.document.get(&*self.normalize(section))
+    }
+
+    /// Build `T` from `section` with [FromSection] implementation
+    ///
+    /// # Errors
+    /// [MappingError] with [GetError::MissingSection] if section is absent, or error of
+    /// [`T::from_section()`](FromSection::from_section) with section name filled in
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{FromSection, GetError, Ini, MappingError, Section};
+    /// struct Db {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// impl FromSection for Db {
+    ///     fn from_section(section: &Section) -> Result<Self, MappingError> {
+    ///         let port = section.try_get("port").map_err(|error| MappingError::key("", "port", error))?;
+    ///         Ok(Db { host: section.get("host").unwrap_or_else(|| "localhost".to_owned()), port })
+    ///     }
+    /// }
+    ///
+    /// let conf = Ini::from_string("[db]\nport = 5432\n[cache]\nport = none").unwrap();
+    ///
+    /// let db: Db = conf.get_section_as("db").unwrap();
+    /// assert_eq!((db.host.as_str(), db.port), ("localhost", 5432));
+    ///
+    /// let error = conf.get_section_as::<Db>("cache").err().unwrap();
+    /// assert_eq!(error.to_string(), "Value `none` cannot be parsed as `u16`: key `port` in section `cache`");
+    /// let error = conf.get_section_as::<Db>("other").err().unwrap();
+    /// assert_eq!(error, MappingError::section("other", GetError::MissingSection));
+    /// ```
+    pub fn get_section_as<T>(&self, section: &str) -> Result<T, MappingError>
+    where
+        T: FromSection,
+    {
+        let found =
+            self.get_section(section).ok_or_else(|| MappingError::section(section, GetError::MissingSection))?;
+        let found = match self.defaults().filter(|defaults| !std::ptr::eq(*defaults, found)) {
+            Some(defaults) => {
+                let mut merged = found.clone();
+                for (key, value) in defaults.values.iter() {
+                    if merged.values.get(key).is_none() {
+                        merged.values.insert(key.clone(), value.clone());
+                    }
+                }
+                Cow::Owned(merged)
+            }
+            None => Cow::Borrowed(found),
+        };
+        T::from_section(&found).map_err(|error| error.in_section(section))
+    }
+
+    /// Replace keys of `section` with keys written by [ToSection] implementation of `value`
+    ///
+    /// Section keeps its position and comments, comments of keys which are not written are removed.
+    /// Absent section is added to the end of document.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, Section, ToSection};
+    /// struct Db {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// impl ToSection for Db {
+    ///     fn to_section(&self) -> Section {
+    ///         Section::new().set("host", &self.host).set("port", self.port)
+    ///     }
+    /// }
+    ///
+    /// let mut conf = Ini::from_string("[db]\nhost = old\nuser = admin\n[app]\nname = test").unwrap();
+    /// conf.set_section_from("db", &Db { host: "localhost".to_owned(), port: 5432 });
+    ///
+    /// assert_eq!(conf.to_string(), "[db]\nhost = localhost\nport = 5432\n\n[app]\nname = test\n");
+    /// ```
+    pub fn set_section_from<T>(&mut self, section: &str, value: &T)
+    where
+        T: ToSection + ?Sized,
+    {
+        let name = self.normalize(section).into_owned();
+        self.list_separators.remove(&name);
+        let target = self.section_mut(name.clone());
+        target.values = OrderedHashMap::new();
+        for (key, value) in value.to_section() {
+            target.insert(&key, value);
+        }
+        // comments of dropped keys must not come back if the key is added again
+        let kept: HashSet<String> = target.values.keys().cloned().collect();
+        if let Some(comments) = self.inline_comments.get_mut(&name) {
+            comments.retain(|key, _| kept.contains(key));
+        }
+        self.leading_comments
+            .retain(|(section, key), _| *section != name || !matches!(key, Some(key) if !kept.contains(key)));
+        self.modified = true;
+    }
+
+    /// Get mutable `section` to edit it independently of document
+    ///
+    /// Document is considered changed after this call if section is present, see [`is_modified()`](Ini::is_modified).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[a]\nb = 1\nc = 2").unwrap();
+    /// if let Some(section) = conf.get_section_mut("a") {
+    ///     section.remove("b");
+    ///     section.insert("d", 3);
+    /// }
+    ///
+    /// assert_eq!(conf.to_string(), "[a]\nc = 2\nd = 3\n");
+    /// ```
+    pub fn get_section_mut(&mut self, section: &str) -> Option<&mut Section> {
+        let section = self.document.get_mut(&*self.normalize(section));
+        self.modified |= section.is_some();
+        section
+    }
+
+    /// Get mutable value of `key` in `section` to edit it in place
+    ///
+    /// Document is considered changed after this call if key is present, see [`is_modified()`](Ini::is_modified).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[section]\npath = /usr/bin").unwrap();
+    ///
+    /// if let Some(path) = conf.get_raw_mut("section", "path") {
+    ///     path.push_str(":/opt/bin");
+    /// }
+    /// assert_eq!(conf.to_string(), "[section]\npath = /usr/bin:/opt/bin\n");
+    /// ```
+    pub fn get_raw_mut(&mut self, section: &str, key: &str) -> Option<&mut String> {
+        let (section, key) = (self.normalize(section).into_owned(), self.normalize(key).into_owned());
+        let value = self.document.get_mut(&section).and_then(|s| s.values.get_mut(&key));
+        self.modified |= value.is_some();
+        value
+    }
+
+    /// Get trailing comment of `key` in `section`, if it was present in the parsed input.
+    ///
+    /// Text after `;` or `#` is never a part of the value, but it is kept and can be read back with this method.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\nport = 8080 ; default port\nhost = localhost").unwrap();
+    ///
+    /// let port: Option<u16> = conf.get("section", "port");
+    ///
+    /// assert_eq!(port, Some(8080));
+    /// assert_eq!(conf.inline_comment("section", "port"), Some("default port"));
+    /// assert_eq!(conf.inline_comment("section", "host"), None);
+    /// ```
+    pub fn inline_comment(&self, section: &str, key: &str) -> Option<&str> {
+        let comments = self.inline_comments.get(&*self.normalize(section));
+        comments.and_then(|s| s.get(&*self.normalize(key))).map(String::as_str)
+    }
+
+    /// Get trailing comment of `section` header, if it was present in the parsed input.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[network]   ; adapter settings\nmtu = 1500\n[dns] # resolvers").unwrap();
+    ///
+    /// assert_eq!(conf.section_comment("network"), Some("adapter settings"));
+    /// assert_eq!(conf.section_comment("dns"), Some("resolvers"));
+    /// assert_eq!(conf.section_comment("other"), None);
+    /// ```
+    pub fn section_comment(&self, section: &str) -> Option<&str> {
+        self.section_comments.get(&*self.normalize(section)).map(String::as_str)
+    }
+
+    /// Substitute references to other keys in all values.
+    ///
+    /// Reference `${section:key}` is replaced by value of `key` in `section`, `${key}` refers to the key in the
+    /// same section, and `$$` is replaced by `$`. Referenced values are interpolated too.
+    ///
+    /// # Errors
+    /// This function will return [Error::Interpolation] if referenced key is missing, reference is not closed
+    /// or references form a cycle
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string(["[paths]",
+    ///                              "home = /home/user",
+    ///                              "data = ${home}/data",
+    ///                              "[log]",
+    ///                              "file = ${paths:data}/app.log",
+    ///                              "price = $$5"].join("\n")).unwrap();
+    ///
+    /// let conf = conf.interpolated().unwrap();
+    ///
+    /// let file: Option<String> = conf.get("log", "file");
+    /// let price: Option<String> = conf.get("log", "price");
+    /// assert_eq!(file.as_deref(), Some("/home/user/data/app.log"));
+    /// assert_eq!(price.as_deref(), Some("$5"));
+    ///
+    /// assert!(Ini::from_string("[a]\nb = ${c}\nc = ${b}").unwrap().interpolated().is_err());
+    /// ```
+    pub fn interpolated(mut self) -> Result<Ini, Error> {
+        let mut resolved = Vec::new();
+        let mut resolver = Resolver::new(&self);
+        for (section, values) in self.document.iter() {
+            for (key, _) in values.iter() {
+                resolved.push((section.clone(), key.clone(), resolver.resolve(section, key)?));
+            }
+        }
+        for (section, key, value) in resolved {
+            if let Some(old) = self.document.get_mut(&section).and_then(|s| s.values.get_mut(&key)) {
+                self.modified |= *old != value;
+                *old = value;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Substitute environment variables `$NAME` and `${NAME}` in all values, `$$` is replaced by `$`.
+    ///
+    /// References to unset variables are handled according to `policy`.
+    ///
+    /// # Errors
+    /// This function will return [Error::Interpolation] if variable is not set and `policy` is [UnsetEnvPolicy::Error]
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Ini, UnsetEnvPolicy};
+    /// let conf = Ini::from_string("[a]\nb = ${TINI_SURELY_UNSET}/$$1\nc = $TINI_SURELY_UNSET").unwrap();
+    ///
+    /// let conf = conf.expand_env(UnsetEnvPolicy::Empty).unwrap();
+    ///
+    /// let b: Option<String> = conf.get("a", "b");
+    /// let c: Option<String> = conf.get("a", "c");
+    /// assert_eq!(b.as_deref(), Some("/$1"));
+    /// assert_eq!(c.as_deref(), Some(""));
+    ///
+    /// assert!(conf.section("a").item("d", "$TINI_SURELY_UNSET").expand_env(UnsetEnvPolicy::Error).is_err());
+    /// ```
+    pub fn expand_env(mut self, policy: UnsetEnvPolicy) -> Result<Ini, Error> {
+        for (section, values) in self.document.iter_mut() {
+            for (key, value) in values.values.iter_mut() {
+                let expanded = expand_env(value, policy, |name| std::env::var(name).ok())
+                    .map_err(|variable| InterpolationError::UnsetVariable(section.clone(), key.clone(), variable))?;
+                self.modified |= *value != expanded;
+                *value = expanded;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Override values with environment variables named `PREFIX_SECTION__KEY`
+    ///
+    /// Section and key names are matched to existing ones ignoring case, absent sections and keys are added
+    /// in lower case. Variables are applied in order of their names, variables with non-unicode names or values
+    /// are skipped. To keep overrides apart from file values, apply them to an empty document and add it
+    /// as a [Layered] layer.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_file("app.ini").unwrap();
+    ///
+    /// // e.g. MYAPP_SERVER__PORT=9000 sets key `Port` of section `Server` or adds `port` to section `server`
+    /// conf.apply_env_overrides("MYAPP");
+    /// ```
+    pub fn apply_env_overrides(&mut self, prefix: &str) {
+        let mut vars: Vec<(String, String)> = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+        vars.sort();
+        self.apply_env_vars(prefix, vars)
+    }
+
+    /// Private method which applies overrides from variables `vars`, see [`apply_env_overrides()`](Ini::apply_env_overrides)
+    fn apply_env_vars(&mut self, prefix: &str, vars: Vec<(String, String)>) {
+        let prefix = format!("{}_", prefix);
+        for (name, value) in vars {
+            let (section, key) = match name.strip_prefix(&prefix).and_then(|rest| rest.split_once("__")) {
+                Some((section, key)) if !section.is_empty() && !key.is_empty() => (section, key),
+                _ => continue,
+            };
+            let section = match self.section_names().find(|s| s.eq_ignore_ascii_case(section)) {
+                Some(name) => name.to_owned(),
+                None => section.to_lowercase(),
+            };
+            let key = match self.get_section(&section).and_then(|s| s.keys().find(|k| k.eq_ignore_ascii_case(key))) {
+                Some(name) => name.to_owned(),
+                None => key.to_lowercase(),
+            };
+            self.set(&section, &key, value);
+        }
+    }
+
+    /// Override values with `section.key=value` strings, e.g. values of `--set` command line options
+    ///
+    /// Path is split into section and key on the last `.`, path and value are trimmed. Path `.key` refers to
+    /// keys before the first section header (see [`global_section()`](ParserConfig::global_section)).
+    /// Overrides are applied in order, so the last one for a key wins.
+    ///
+    /// # Errors
+    /// [OverrideError] with index and text of the first malformed override, document is left unchanged then
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{Error, Ini, OverrideError};
+    /// let mut conf = Ini::from_string("[server]\nport = 80").unwrap();
+    ///
+    /// let args = ["server.port=8080", "log.file = /tmp/app.log", "server.port=9000"];
+    /// conf.apply_overrides(args).unwrap();
+    /// assert_eq!(conf.to_string(), "[server]\nport = 9000\n\n[log]\nfile = /tmp/app.log\n");
+    ///
+    /// match conf.apply_overrides(["a.b=1", "verbose"]) {
+    ///     Err(Error::Override(error)) => {
+    ///         assert_eq!(error, OverrideError::MissingValue(1, "verbose".to_owned()));
+    ///         assert_eq!(error.to_string(), "Override #2 `verbose` has no `=` between path and value");
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// assert!(!conf.has_section("a"));
+    /// ```
+    pub fn apply_overrides<I, S>(&mut self, overrides: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut parsed = Vec::new();
+        for (index, text) in overrides.into_iter().enumerate() {
+            let text = text.as_ref();
+            let (path, value) =
+                text.split_once('=').ok_or_else(|| OverrideError::MissingValue(index, text.to_owned()))?;
+            let (section, key) = match path.trim().rsplit_once('.') {
+                Some((section, key)) if !key.trim().is_empty() => (section.trim(), key.trim().to_owned()),
+                _ => return Err(OverrideError::InvalidPath(index, text.to_owned()).into()),
+            };
+            let section = if section.is_empty() { self.global_section.clone() } else { section.to_owned() };
+            parsed.push((section, key, value.trim().to_owned()));
+        }
+        for (section, key, value) in parsed {
+            self.set(&section, &key, value);
+        }
+        Ok(())
+    }
+
+    /// Check if `section` is present, even if it has no keys.
+    ///
+    /// Sections are created by headers in parsed input or by the first [`item()`](Ini::item) call.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[empty]\n[section]\none = 1").unwrap();
+    ///
+    /// assert!(conf.has_section("empty"));
+    /// assert!(conf.has_section("section"));
+    /// assert!(!conf.has_section("other"));
+    /// ```
+    pub fn has_section(&self, section: &str) -> bool {
+        self.document.contains_key(&*self.normalize(section))
+    }
+
+    /// Check if `key` is present in `section`, regardless of its value.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\nempty =\none = 1").unwrap();
+    ///
+    /// assert!(conf.has_key("section", "empty"));
+    /// assert!(conf.has_key("section", "one"));
+    /// assert!(!conf.has_key("section", "two"));
+    /// assert!(!conf.has_key("other", "one"));
+    /// ```
+    pub fn has_key(&self, section: &str, key: &str) -> bool {
+        self.get_raw(section, key).is_some()
+    }
+
+    /// Get scalar value of key in section.
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\none = 1").unwrap();
+    ///
+    /// let value: Option<u8> = conf.get("section", "one");
+    ///
+    /// assert_eq!(value, Some(1));
+    /// ```
+    pub fn get<T>(&self, section: &str, key: &str) -> Option<T>
+    where
+        T: FromIniValue,
+    {
+        self.get_raw(section, key).and_then(T::parse_value)
+    }
+
+    /// Get value of key in section converted by `parse`, for types which don't implement [FromIniValue]
+    /// or need non-standard parsing
+    ///
+    /// Returns [None](Option::None) if key is absent or `parse` fails, like [`get()`](Ini::get).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[limits]\nrows = 1_000_000\nratio = 0,75").unwrap();
+    ///
+    /// let rows = conf.get_with("limits", "rows", |raw| raw.replace('_', "").parse::<u32>());
+    /// let ratio = conf.get_with("limits", "ratio", |raw| raw.replace(',', ".").parse::<f64>());
+    /// assert_eq!(rows, Some(1_000_000));
+    /// assert_eq!(ratio, Some(0.75));
+    /// ```
+    pub fn get_with<T, E, F>(&self, section: &str, key: &str, parse: F) -> Option<T>
+    where
+        F: FnOnce(&str) -> Result<T, E>,
+    {
+        self.get_raw(section, key).and_then(|x| parse(x).ok())
+    }
+
+    /// Get value of key in section as path to file or directory
+    ///
+    /// Leading `~` is replaced by home directory of user. Relative path is resolved against `base_dir`
+    /// or, if it is [None](Option::None), against directory of file the document was loaded from
+    /// (see [`source_path()`](Ini::source_path)). Returns [None](Option::None) if key is absent or empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::path::Path;
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[log]\nfile = logs/app.log\nroot = /var/log").unwrap();
+    ///
+    /// let file = conf.get_file_path("log", "file", Some(Path::new("/etc/app")));
+    /// assert_eq!(file.as_deref(), Some(Path::new("/etc/app/logs/app.log")));
+    /// let root = conf.get_file_path("log", "root", Some(Path::new("/etc/app")));
+    /// assert_eq!(root.as_deref(), Some(Path::new("/var/log")));
+    /// // document is not loaded from file, so path is kept relative
+    /// assert_eq!(conf.get_file_path("log", "file", None).as_deref(), Some(Path::new("logs/app.log")));
+    /// ```
+    pub fn get_file_path(&self, section: &str, key: &str, base_dir: Option<&Path>) -> Option<PathBuf> {
+        let raw = self.get_raw(section, key).filter(|x| !x.is_empty())?;
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+        let path = match (raw.strip_prefix('~'), home) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(&['/', '\\'][..]) => {
+                Path::new(&home).join(rest.trim_start_matches(&['/', '\\'][..]))
+            }
+            _ => PathBuf::from(raw),
+        };
+        let base = base_dir.or_else(|| self.source.as_deref().and_then(Path::parent));
+        match base {
+            Some(base) if path.is_relative() => Some(base.join(path)),
+            _ => Some(path),
+        }
+    }
+
+    /// Get scalar value by `path` of section and key joined with `.`
+    ///
+    /// Path is split by the last `.`, so section name may contain dots, but key may not.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[server]\nport = 8080\n[log.file]\npath = app.log").unwrap();
+    ///
+    /// assert_eq!(conf.get_path::<u16>("server.port"), Some(8080));
+    /// assert_eq!(conf.get_path::<String>("log.file.path").as_deref(), Some("app.log"));
+    /// assert_eq!(conf.get_path::<u16>("port"), None);
+    /// ```
+    pub fn get_path<T>(&self, path: &str) -> Option<T>
+    where
+        T: FromIniValue,
+    {
+        path.rsplit_once('.').and_then(|(section, key)| self.get(section, key))
+    }
+
+    /// Get scalar value of key in section, telling apart absent key, empty value and invalid value.
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    ///
+    /// # Errors
+    /// - [GetError::MissingSection] if section is absent;
+    /// - [GetError::MissingKey] if key is absent in section;
+    /// - [GetError::Empty] if value is empty, even if `T` can be parsed from empty string;
+    /// - [GetError::ParseFailed] if value cannot be converted to `T`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{GetError, Ini};
+    /// let conf = Ini::from_string("[section]\none = 1\nempty =\nword = abc").unwrap();
+    ///
+    /// assert_eq!(conf.try_get::<u8>("section", "one"), Ok(1));
+    /// assert_eq!(conf.try_get::<u8>("other", "one"), Err(GetError::MissingSection));
+    /// assert_eq!(conf.try_get::<u8>("section", "two"), Err(GetError::MissingKey));
+    /// assert_eq!(conf.try_get::<String>("section", "empty"), Err(GetError::Empty));
+    ///
+    /// let error = conf.try_get::<u8>("section", "word").unwrap_err();
+    /// assert_eq!(error, GetError::ParseFailed { value: "abc".to_owned(), type_name: "u8" });
+    /// assert_eq!(error.to_string(), "Value `abc` cannot be parsed as `u8`");
+    /// ```
+    pub fn try_get<T>(&self, section: &str, key: &str) -> Result<T, GetError>
+    where
+        T: FromIniValue,
+    {
+        value::try_parse(self.lookup(section, key)?)
+    }
+
+    /// Get scalar value of key in section or `default` if key is absent or its value cannot be converted to `T`
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[server]\nport = 8080\nworkers = many").unwrap();
+    ///
+    /// assert_eq!(conf.get_or("server", "port", 80), 8080);
+    /// assert_eq!(conf.get_or("server", "timeout", 30), 30);
+    /// assert_eq!(conf.get_or("server", "workers", 4), 4);
+    /// ```
+    pub fn get_or<T>(&self, section: &str, key: &str, default: T) -> T
+    where
+        T: FromIniValue,
+    {
+        self.get(section, key).unwrap_or(default)
+    }
+
+    /// Get scalar value of key in section, adding result of `default` to document if key is absent
+    ///
+    /// Section and key are added to the end if they are absent. Value which cannot be converted to `T`
+    /// is kept in document and result of `default` is returned instead.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[server]\nport = 8080\nworkers = many").unwrap();
+    ///
+    /// assert_eq!(conf.get_or_insert_with("server", "port", || 80), 8080);
+    /// assert_eq!(conf.get_or_insert_with("server", "timeout", || 30), 30);
+    /// assert_eq!(conf.get_or_insert_with("server", "workers", || 4), 4);
+    /// assert_eq!(conf.to_string(), "[server]\nport = 8080\nworkers = many\ntimeout = 30\n");
+    /// ```
+    pub fn get_or_insert_with<T, F>(&mut self, section: &str, key: &str, default: F) -> T
+    where
+        T: IniValue,
+        F: FnOnce() -> T,
+    {
+        if self.has_key(section, key) {
+            return self.get(section, key).unwrap_or_else(default);
+        }
+        let value = default();
+        self.set(section, key, value.format_value());
+        value
+    }
+
+    /// Like [`get_or()`](Ini::get_or), but reports values which cannot be converted to `T` instead of
+    /// replacing them with `default`
+    ///
+    /// `default` is returned if key is absent or its value is empty.
+    ///
+    /// # Errors
+    /// [GetError::ParseFailed] if value cannot be converted to `T`
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{GetError, Ini};
+    /// let conf = Ini::from_string("[server]\nport = 8080\nhost =\nworkers = many").unwrap();
+    ///
+    /// assert_eq!(conf.try_get_or("server", "port", 80), Ok(8080));
+    /// assert_eq!(conf.try_get_or("server", "host", "localhost".to_owned()), Ok("localhost".to_owned()));
+    /// assert!(matches!(conf.try_get_or("server", "workers", 4), Err(GetError::ParseFailed { .. })));
+    /// ```
+    pub fn try_get_or<T>(&self, section: &str, key: &str, default: T) -> Result<T, GetError>
+    where
+        T: FromIniValue,
+    {
+        match self.try_get(section, key) {
+            Err(GetError::MissingSection) | Err(GetError::MissingKey) | Err(GetError::Empty) => Ok(default),
+            result => result,
+        }
+    }
+
+    /// Get scalar value of key in git-style section `[section "subsection"]`.
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[remote \"origin\"]\nurl = https://example.com/repo.git").unwrap();
+    ///
+    /// let url: Option<String> = conf.get_sub("remote", "origin", "url");
+    ///
+    /// assert_eq!(url.as_deref(), Some("https://example.com/repo.git"));
+    /// ```
+    pub fn get_sub<T>(&self, section: &str, subsection: &str, key: &str) -> Option<T>
+    where
+        T: FromIniValue,
+    {
+        self.get(&join_subsection(section, subsection), key)
+    }
+
+    /// Get vector value of `key` in `section`. Value should use `,` as separator.
+    ///
+    /// The function returns [None](Option::None) if one of the elements can not be parsed.
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\nlist = 1, 2, 3, 4").unwrap();
+    ///
+    /// let value: Option<Vec<u8>> = conf.get_vec("section", "list");
+    ///
+    /// assert_eq!(value, Some(vec![1, 2, 3, 4]));
+    /// ```
+    pub fn get_vec<T>(&self, section: &str, key: &str) -> Option<Vec<T>>
+    where
+        T: FromIniValue,
     {
         self.get_vec_with_sep(section, key, ",")
     }
@@ -454,7 +2720,7 @@ impl Ini {
     ///
     /// The function returns [None](Option::None) if one of the elements can not be parsed or not found.
     ///
-    /// - output type `T` must implement [FromStr] trait for auto conversion
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
     ///
     /// # Example
     /// ```
@@ -467,10 +2733,148 @@ impl Ini {
     /// ```
     pub fn get_vec_with_sep<T>(&self, section: &str, key: &str, sep: &str) -> Option<Vec<T>>
     where
-        T: FromStr,
+        T: FromIniValue,
     {
         self.get_raw(section, key)
-            .and_then(|x| x.split(sep).map(|s| s.trim().parse()).collect::<Result<Vec<T>, _>>().ok())
+            .and_then(|x| x.split(sep).map(|s| T::parse_value(s.trim())).collect::<Option<Vec<T>>>())
+    }
+
+    /// Get vector value of `key` in `section` separated by `,`, telling apart absent key and invalid elements.
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    ///
+    /// # Errors
+    /// - [GetError::MissingSection] if section is absent;
+    /// - [GetError::MissingKey] if key is absent in section;
+    /// - [GetError::Empty] if value is empty;
+    /// - [GetError::InvalidElement] with index and text of the first element which cannot be converted to `T`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::{GetError, Ini};
+    /// let conf = Ini::from_string("[section]\nports = 80, 443, http, 8080").unwrap();
+    ///
+    /// let error = conf.try_get_vec::<u16>("section", "ports").unwrap_err();
+    /// assert_eq!(error, GetError::InvalidElement { index: 2, value: "http".to_owned(), type_name: "u16" });
+    /// assert_eq!(error.to_string(), "Element 2 `http` cannot be parsed as `u16`");
+    ///
+    /// assert_eq!(conf.try_get_vec::<String>("section", "ports").unwrap().len(), 4);
+    /// assert_eq!(conf.try_get_vec::<u16>("section", "other"), Err(GetError::MissingKey));
+    /// ```
+    pub fn try_get_vec<T>(&self, section: &str, key: &str) -> Result<Vec<T>, GetError>
+    where
+        T: FromIniValue,
+    {
+        self.try_get_vec_with_sep(section, key, ",")
+    }
+
+    /// Get vector value of `key` in `section` separated by `sep` string like [`try_get_vec()`](Ini::try_get_vec)
+    ///
+    /// # Errors
+    /// The same as [`try_get_vec()`](Ini::try_get_vec)
+    pub fn try_get_vec_with_sep<T>(&self, section: &str, key: &str, sep: &str) -> Result<Vec<T>, GetError>
+    where
+        T: FromIniValue,
+    {
+        value::try_parse_vec(self.lookup(section, key)?, sep)
+    }
+
+    /// Get vector value of `key` in `section` like [`get_vec()`](Ini::get_vec) or `default` if key is absent
+    /// or one of the elements cannot be parsed
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\nlist = 1, 2, 3").unwrap();
+    ///
+    /// assert_eq!(conf.get_vec_or("section", "list", vec![0u8]), [1, 2, 3]);
+    /// assert_eq!(conf.get_vec_or("section", "other", vec![0u8]), [0]);
+    /// ```
+    pub fn get_vec_or<T>(&self, section: &str, key: &str, default: Vec<T>) -> Vec<T>
+    where
+        T: FromIniValue,
+    {
+        self.get_vec(section, key).unwrap_or(default)
+    }
+
+    /// Get boolean value of key in section.
+    ///
+    /// Unlike [`get::<bool>()`](Ini::get) accepts `true/false`, `yes/no`, `on/off` and `1/0` in any case.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\nverbose = Yes\ncolor = off\ndebug = maybe").unwrap();
+    ///
+    /// assert_eq!(conf.get_bool("section", "verbose"), Some(true));
+    /// assert_eq!(conf.get_bool("section", "color"), Some(false));
+    /// assert_eq!(conf.get_bool("section", "debug"), None);
+    /// ```
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        self.get_raw(section, key).and_then(value::parse_bool)
+    }
+
+    /// Get integer value of key in section.
+    ///
+    /// Value may have `0x`, `0o` or `0b` prefix for hexadecimal, octal or binary radix,
+    /// and `_` separators between digits. Values out of range of `T` are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[file]\nmode = 0o755\nmask = 0xFF_FF\nsize = 1_000\nflags = 0b1000_0000").unwrap();
+    ///
+    /// assert_eq!(conf.get_int::<u32>("file", "mode"), Some(0o755));
+    /// assert_eq!(conf.get_int::<u16>("file", "mask"), Some(0xFFFF));
+    /// assert_eq!(conf.get_int::<i64>("file", "size"), Some(1000));
+    /// assert_eq!(conf.get_int::<i8>("file", "flags"), None);
+    /// ```
+    pub fn get_int<T>(&self, section: &str, key: &str) -> Option<T>
+    where
+        T: TryFrom<i128>,
+    {
+        self.get_raw(section, key).and_then(value::parse_int).and_then(|x| T::try_from(x).ok())
+    }
+
+    /// Get duration value of key in section.
+    ///
+    /// Value is a sequence of integers with units `d`, `h`, `m`, `s`, `ms`, `us` (or `µs`) and `ns`,
+    /// optionally separated by spaces. Plain integer without unit is a number of seconds.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[net]\nconnect = 500ms\nidle = 1h30m\nretry = 5").unwrap();
+    ///
+    /// assert_eq!(conf.get_duration("net", "connect"), Some(Duration::from_millis(500)));
+    /// assert_eq!(conf.get_duration("net", "idle"), Some(Duration::from_secs(5400)));
+    /// assert_eq!(conf.get_duration("net", "retry"), Some(Duration::from_secs(5)));
+    /// ```
+    pub fn get_duration(&self, section: &str, key: &str) -> Option<Duration> {
+        self.get_raw(section, key).and_then(value::parse_duration)
+    }
+
+    /// Get size in bytes of key in section.
+    ///
+    /// Value is a number, optionally with fraction, followed by case-insensitive unit:
+    /// - no unit or `B` for bytes;
+    /// - `K`, `M`, `G`, `T`, `P`, `E` or `KiB`, `MiB`, `GiB`, `TiB`, `PiB`, `EiB` for binary units (powers of 1024);
+    /// - `KB`, `MB`, `GB`, `TB`, `PB`, `EB` for decimal units (powers of 1000).
+    ///
+    /// Fractional bytes are truncated, values larger than [u64::MAX] are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[cache]\nmemory = 4MiB\ndisk = 1.5GB\nentry = 10K").unwrap();
+    ///
+    /// assert_eq!(conf.get_size("cache", "memory"), Some(4 * 1024 * 1024));
+    /// assert_eq!(conf.get_size("cache", "disk"), Some(1_500_000_000));
+    /// assert_eq!(conf.get_size("cache", "entry"), Some(10240));
+    /// ```
+    pub fn get_size(&self, section: &str, key: &str) -> Option<u64> {
+        self.get_raw(section, key).and_then(value::parse_size)
     }
 
     /// An iterator visiting all key-value pairs in order of appearance in section.
@@ -489,11 +2893,227 @@ impl Ini {
     /// assert_eq!(search.next(), Some((&"dd".to_string(), &"duckduckgo.com".to_string())));
     /// assert_eq!(search.next(), None);
     ///
-    /// assert_eq!(conf.section_iter("absent").count(), 0);
+    /// assert_eq!(conf.section_iter("absent").count(), 0);
+    /// ```
+    pub fn section_iter(&self, section: &str) -> SectionIter<'_> {
+        let section = self.document.get(&*self.normalize(section)).unwrap_or(&self.empty_section);
+        section.iter()
+    }
+
+    /// Iterate over keys of section starting with `prefix` in order of appearance, see [`Section::with_prefix()`]
+    ///
+    /// If section with given name doesn't exist in document, method returns empty iterator
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[net]\ndns_1 = 1.1.1.1\ndns_2 = 8.8.8.8\nproxy = none").unwrap();
+    ///
+    /// let servers: Vec<&str> = conf.section_iter_prefix("net", "dns_", true).map(|(_, server)| server).collect();
+    /// assert_eq!(servers, ["1.1.1.1", "8.8.8.8"]);
+    /// assert_eq!(conf.section_iter_prefix("absent", "dns_", true).count(), 0);
+    /// ```
+    pub fn section_iter_prefix<'a>(
+        &'a self, section: &str, prefix: &str, strip: bool,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        let section = self.document.get(&*self.normalize(section)).unwrap_or(&self.empty_section);
+        section.with_prefix(prefix, strip)
+    }
+
+    /// A mutable iterator visiting all key-value pairs in order of appearance in section.
+    ///
+    /// If section with given name doesn't exist in document, method returns empty iterator.
+    /// Document is considered changed after this call if section is present, see [`is_modified()`](Ini::is_modified).
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[paths]\ndata = data\nlogs = logs\n[other]\nx = data").unwrap();
+    ///
+    /// for (_, value) in conf.section_iter_mut("paths") {
+    ///     value.insert_str(0, "/var/lib/app/");
+    /// }
+    /// assert_eq!(conf.to_string(), "[paths]\ndata = /var/lib/app/data\nlogs = /var/lib/app/logs\n\n[other]\nx = data\n");
+    ///
+    /// assert_eq!(conf.section_iter_mut("absent").count(), 0);
+    /// ```
+    pub fn section_iter_mut(&mut self, section: &str) -> SectionIterMut<'_> {
+        let name = self.normalize(section).into_owned();
+        match self.document.get_mut(&name) {
+            Some(section) => {
+                self.modified = true;
+                section.iter_mut()
+            }
+            None => self.empty_section.iter_mut(),
+        }
+    }
+
+    /// Iterate over direct subsections of `section` in order of appearance, yielding their names and sections.
+    ///
+    /// Sections are not nested in document: `[server.http]` is a separate section with dotted name, which is
+    /// treated as a subsection of `server` only by this method. Parent section doesn't need to exist.
+    /// Intermediate names without header of their own, like `server.ftp` for `[server.ftp.tls]`, are yielded
+    /// with `None` section, so deeper levels can be listed by calling this method with the yielded name.
+    /// Values of nested sections are accessed with full name, e.g. `get("server.http", "port")`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string(["[server]",
+    ///                              "name = main",
+    ///                              "[server.http]",
+    ///                              "port = 80",
+    ///                              "[server.ftp.tls]",
+    ///                              "port = 990",
+    ///                              "[server.http.tls]",
+    ///                              "port = 443"].join("\n")).unwrap();
+    ///
+    /// let children: Vec<(&str, bool)> = conf.subsections("server").map(|(name, s)| (name, s.is_some())).collect();
+    /// assert_eq!(children, [("server.http", true), ("server.ftp", false)]);
+    ///
+    /// let tls: Vec<&str> = conf.subsections("server.ftp").map(|(name, _)| name).collect();
+    /// assert_eq!(tls, ["server.ftp.tls"]);
+    ///
+    /// let port: Option<u16> = conf.get("server.http", "port");
+    /// assert_eq!(port, Some(80));
+    /// ```
+    pub fn subsections<'a>(&'a self, section: &str) -> impl Iterator<Item = (&'a str, Option<&'a Section>)> + 'a {
+        let prefix = format!("{}.", self.normalize(section));
+        let mut seen = Vec::new();
+        self.document
+            .keys()
+            .filter_map(move |name| {
+                let rest = name.strip_prefix(prefix.as_str())?;
+                let end = prefix.len() + rest.find('.').unwrap_or(rest.len());
+                Some(&name[..end])
+            })
+            .filter(move |name| {
+                if seen.contains(name) {
+                    return false;
+                }
+                seen.push(*name);
+                true
+            })
+            .map(move |name| (name, self.document.get(name)))
+    }
+
+    /// Iterate over sections with names matching glob `pattern` in order of appearance
+    ///
+    /// In pattern `*` matches any sequence of characters and `?` matches any single character.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[host:web1]\nip = 10.0.0.1\n[db]\nip = 10.0.0.5\n[host:web2]\nip = 10.0.0.2").unwrap();
+    ///
+    /// let ips: Vec<(&str, &str)> = conf.sections_matching("host:*").map(|(name, s)| (name, &s["ip"])).collect();
+    /// assert_eq!(ips, [("host:web1", "10.0.0.1"), ("host:web2", "10.0.0.2")]);
+    /// ```
+    pub fn sections_matching<'a>(&'a self, pattern: &str) -> impl Iterator<Item = (&'a str, &'a Section)> + 'a {
+        let pattern = self.normalize(pattern).into_owned();
+        self.sections_where(move |name| glob_match(&pattern, name))
+    }
+
+    /// Iterate over sections with names for which `predicate` returns `true` in order of appearance
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[web1]\n[db]\n[web2]").unwrap();
+    ///
+    /// let names: Vec<&str> = conf.sections_where(|name| name.starts_with("web")).map(|(name, _)| name).collect();
+    /// assert_eq!(names, ["web1", "web2"]);
+    /// ```
+    pub fn sections_where<'a, F>(&'a self, mut predicate: F) -> impl Iterator<Item = (&'a str, &'a Section)> + 'a
+    where
+        F: FnMut(&str) -> bool + 'a,
+    {
+        self.document.iter().filter(move |(name, _)| predicate(name)).map(|(name, section)| (name.as_str(), section))
+    }
+
+    /// Iterate over sections containing `key` in order of appearance, yielding pairs of section name and value
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[db]\npassword = secret\n[app]\nname = test\n[mail]\npassword = 123").unwrap();
+    ///
+    /// let found: Vec<(&str, &str)> = conf.find_key("password").collect();
+    /// assert_eq!(found, [("db", "secret"), ("mail", "123")]);
+    ///
+    /// let sections: Vec<String> = conf.find_key("password").map(|(section, _)| section.to_owned()).collect();
+    /// for section in sections {
+    ///     conf.set(&section, "password", "***");
+    /// }
+    /// assert_eq!(conf.get_raw("mail", "password"), Some("***"));
+    /// ```
+    pub fn find_key<'a>(&'a self, key: &str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        let key = self.normalize(key).into_owned();
+        self.document
+            .iter()
+            .filter_map(move |(name, section)| section.values.get(&key).map(|value| (name.as_str(), value.as_str())))
+    }
+
+    /// Iterate over subsection names of git-style sections `[section "subsection"]` in order of appearance.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string(["[remote \"origin\"]",
+    ///                              "url = https://example.com/repo.git",
+    ///                              "[branch \"main\"]",
+    ///                              "remote = origin",
+    ///                              "[remote \"upstream\"]",
+    ///                              "url = https://example.com/upstream.git"].join("\n")).unwrap();
+    ///
+    /// let remotes: Vec<String> = conf.quoted_subsections("remote").collect();
+    /// assert_eq!(remotes, ["origin", "upstream"]);
+    /// ```
+    pub fn quoted_subsections<'a>(&'a self, section: &'a str) -> impl Iterator<Item = String> + 'a {
+        self.document
+            .keys()
+            .filter_map(|name| split_subsection(name))
+            .filter(move |(kind, _)| *kind == section)
+            .map(|(_, subsection)| subsection)
+    }
+
+    /// Iterate over names of all sections in order of appearance
+    ///
+    /// Keys before the first section header belong to section with empty name
+    /// (see [`global_section()`](ParserConfig::global_section)), which is yielded if it is not empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[b]\nx = 1\n[a]\ny = 2").unwrap();
+    ///
+    /// let names: Vec<&str> = conf.section_names().collect();
+    /// assert_eq!(names, ["b", "a"]);
+    /// ```
+    pub fn section_names(&self) -> impl Iterator<Item = &str> {
+        self.document.keys().map(String::as_str)
+    }
+
+    /// Number of sections in document, including sections without keys
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[empty]\n[section]\none = 1\ntwo = 2").unwrap();
+    ///
+    /// assert_eq!(conf.len(), 2);
+    /// assert_eq!(conf["section"].len(), 2);
+    /// assert!(!conf.is_empty());
+    /// assert!(Ini::new().is_empty());
+    /// assert!(Ini::from_string("; only comment").unwrap().is_empty());
     /// ```
-    pub fn section_iter(&self, section: &str) -> SectionIter {
-        let section = self.document.get(section).unwrap_or(&self.empty_section);
-        SectionIter { document: &section, iter: section.iter() }
+    pub fn len(&self) -> usize {
+        self.document.keys().len()
+    }
+
+    /// Check if document has no sections
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     /// Iterate over all sections in order of appearance, yielding pairs of
@@ -513,10 +3133,10 @@ impl Ini {
     ///     match name.as_str() {
     ///         "foo" => assert_eq!(section_iter.count(), 2),
     ///         "bar" => assert_eq!(section_iter.count(), 1),
-    ///         _ => assert!(false),
+    ///         _ => unreachable!(),
     ///     }
     /// }
-    pub fn iter(&self) -> IniIter {
+    pub fn iter(&self) -> IniIter<'_> {
         IniIter { iter: self.document.iter() }
     }
 
@@ -544,25 +3164,271 @@ impl Ini {
     ///         assert_eq!(val.as_str(), "replaced");
     ///     }
     /// }
-    pub fn iter_mut(&mut self) -> IniIterMut {
+    pub fn iter_mut(&mut self) -> IniIterMut<'_> {
+        // values may be changed through iterator
+        self.modified = true;
         IniIterMut { iter: self.document.iter_mut() }
     }
 }
 
 impl fmt::Display for Ini {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut items = Vec::new();
-        for (name, section) in self.iter() {
-            // insert section block
-            items.push(format!("[{}]", name));
-            // add items
-            for (key, value) in section {
-                items.push(format!("{} = {}", key, value));
+        self.write_with(&self.format, f)
+    }
+}
+
+/// Ini with output style, written directly to formatter or writer without intermediate string
+struct Formatted<'a>(&'a Ini, &'a FormatOptions);
+
+impl fmt::Display for Formatted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_with(self.1, f)
+    }
+}
+
+/// Check that name is not empty, not padded with whitespace and has no line breaks, comments and `forbidden` chars
+fn is_valid_name(name: &str, forbidden: &[char]) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && !name.contains(|c| matches!(c, '\n' | '\r' | ';' | '#') || forbidden.contains(&c))
+}
+
+/// UTF-8 byte order mark
+const BOM: &str = "\u{feff}";
+
+/// Break line on spaces after `start` position so that every part fits into `width` columns
+fn wrap_line(line: &str, start: usize, width: usize) -> Vec<String> {
+    let columns = |s: &str| s.chars().count();
+    if columns(line) <= width {
+        return vec![line.to_owned()];
+    }
+    let (head, value) = line.split_at(start);
+    let mut parts = Vec::new();
+    let mut current = head.to_owned();
+    for (i, word) in value.split(' ').enumerate() {
+        if i > 0 && !word.is_empty() && !current.trim().is_empty() && columns(&current) + columns(word) + 3 > width {
+            parts.push(format!("{} \\", current));
+            current = format!("    {}", word);
+        } else {
+            if i > 0 {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+/// Get section by name
+///
+/// # Panics
+/// Panics if section is absent, use [`section_iter()`](Ini::section_iter) or [`get()`](Ini::get)
+/// to handle absent sections
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let conf = Ini::from_string("[server]\nhost = localhost\nport = 80").unwrap();
+///
+/// assert_eq!(conf["server"].len(), 2);
+/// assert_eq!(&conf["server"]["port"], "80");
+/// ```
+impl Index<&str> for Ini {
+    type Output = Section;
+
+    fn index(&self, section: &str) -> &Section {
+        match self.document.get(&*self.normalize(section)) {
+            Some(section) => section,
+            None => panic!("section `{}` is not found", section),
+        }
+    }
+}
+
+/// Get mutable section by name, document is considered changed after this call
+///
+/// # Panics
+/// Panics if section is absent, use [`entry()`](Ini::entry) to add absent sections
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let mut conf = Ini::from_string("[server]\nhost = localhost").unwrap();
+/// conf["server"].entry("port").or_insert(80);
+///
+/// assert_eq!(conf.to_string(), "[server]\nhost = localhost\nport = 80\n");
+/// ```
+impl IndexMut<&str> for Ini {
+    fn index_mut(&mut self, section: &str) -> &mut Section {
+        let name = self.normalize(section).into_owned();
+        self.modified = true;
+        match self.document.get_mut(&name) {
+            Some(section) => section,
+            None => panic!("section `{}` is not found", name),
+        }
+    }
+}
+
+/// Collect document from pairs of section name and key-value pairs
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let conf: Ini = vec![("a", vec![("x", 1), ("y", 2)]), ("b", vec![("z", 3)])].into_iter().collect();
+///
+/// assert_eq!(conf.to_string(), "[a]\nx = 1\ny = 2\n\n[b]\nz = 3\n");
+/// ```
+impl<S, I, K, V> FromIterator<(S, I)> for Ini
+where
+    S: Into<String>,
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: ToIniValue,
+{
+    fn from_iter<T: IntoIterator<Item = (S, I)>>(iter: T) -> Self {
+        let mut ini = Ini::new();
+        ini.extend(iter);
+        ini
+    }
+}
+
+/// Add keys to sections like [`set()`](Ini::set), sections and keys are added to the end if they are absent
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let mut conf = Ini::from_string("[a]\nx = 1").unwrap();
+/// conf.extend(vec![("a", vec![("x", 2), ("y", 3)]), ("b", vec![("z", 4)])]);
+///
+/// assert_eq!(conf.to_string(), "[a]\nx = 2\ny = 3\n\n[b]\nz = 4\n");
+/// ```
+impl<S, I, K, V> Extend<(S, I)> for Ini
+where
+    S: Into<String>,
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: ToIniValue,
+{
+    fn extend<T: IntoIterator<Item = (S, I)>>(&mut self, iter: T) {
+        for (section, items) in iter {
+            let section = section.into();
+            for (key, value) in items {
+                self.set(&section, &key.into(), value);
             }
-            // and blank line between sections
-            items.push("".to_string());
         }
-        write!(f, "{}", items.join("\n"))
+    }
+}
+
+/// Convert document into map of sections, each of them maps keys to values
+impl From<Ini> for HashMap<String, HashMap<String, String>> {
+    fn from(ini: Ini) -> Self {
+        ini.into_iter().map(|(name, section)| (name, section.into_iter().collect())).collect()
+    }
+}
+
+/// Convert document into map of sections sorted by name, each of them maps keys to values
+///
+/// # Example
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use tini::Ini;
+/// let conf = Ini::from_string("[b]\nz = 3\n[a]\ny = 2\nx = 1\n[empty]").unwrap();
+///
+/// let map = BTreeMap::from(conf.clone());
+/// assert_eq!(map["a"]["x"], "1");
+/// assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b", "empty"]);
+///
+/// assert_eq!(Ini::from(map).to_string(), "[a]\nx = 1\ny = 2\n\n[b]\nz = 3\n\n[empty]\n");
+/// ```
+impl From<Ini> for BTreeMap<String, BTreeMap<String, String>> {
+    fn from(ini: Ini) -> Self {
+        ini.into_iter().map(|(name, section)| (name, section.into_iter().collect())).collect()
+    }
+}
+
+/// Build document from map of sections, keeping sections without keys
+///
+/// Map has no order, so sections and keys are sorted by name.
+impl From<HashMap<String, HashMap<String, String>>> for Ini {
+    fn from(map: HashMap<String, HashMap<String, String>>) -> Self {
+        let sorted: BTreeMap<String, BTreeMap<String, String>> =
+            map.into_iter().map(|(name, section)| (name, section.into_iter().collect())).collect();
+        Ini::from(sorted)
+    }
+}
+
+/// Build document from map of sections in order of map, keeping sections without keys
+impl From<BTreeMap<String, BTreeMap<String, String>>> for Ini {
+    fn from(map: BTreeMap<String, BTreeMap<String, String>>) -> Self {
+        let mut ini = Ini::new();
+        for (name, section) in map {
+            *ini.section_mut(name) = section.into_iter().collect();
+        }
+        ini
+    }
+}
+
+impl PartialEq for Ini {
+    fn eq(&self, other: &Ini) -> bool {
+        self.document.keys().len() == other.document.keys().len()
+            && self.document.iter().all(|(name, section)| other.document.get(name) == Some(section))
+    }
+}
+
+impl Eq for Ini {}
+
+/// Parse document with default options like [`from_string()`](Ini::from_string)
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let conf: Ini = "[a]\nb = 1".parse().unwrap();
+///
+/// assert_eq!(conf.get::<u8>("a", "b"), Some(1));
+/// assert!("[a".parse::<Ini>().is_err());
+/// ```
+impl FromStr for Ini {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Ini, Error> {
+        Ini::parse(s, &ParserConfig::default())
+    }
+}
+
+/// Parse document with default options like [`from_string()`](Ini::from_string)
+///
+/// # Example
+/// ```
+/// # use std::convert::TryFrom;
+/// # use tini::Ini;
+/// let conf = Ini::try_from("[a]\nb = 1").unwrap();
+///
+/// assert_eq!(conf.get::<u8>("a", "b"), Some(1));
+/// ```
+impl TryFrom<&str> for Ini {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Ini, Error> {
+        s.parse()
+    }
+}
+
+/// Load document from file with default options like [`from_file()`](Ini::from_file)
+///
+/// # Example
+/// ```no_run
+/// # use std::convert::TryFrom;
+/// # use std::path::Path;
+/// # use tini::Ini;
+/// let conf = Ini::try_from(Path::new("example.ini"));
+///
+/// assert!(conf.ok().is_some());
+/// ```
+impl TryFrom<&Path> for Ini {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Ini, Error> {
+        Ini::from_file(path)
     }
 }
 
@@ -583,7 +3449,7 @@ impl<'a> Iterator for IniIter<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(name, section)| (name, SectionIter { document: &section, iter: section.iter() }))
+        self.iter.next().map(|(name, section)| (name, section.iter()))
     }
 }
 
@@ -598,11 +3464,73 @@ impl<'a> Iterator for IniIterMut<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(name, section)| (name, SectionIterMut { iter: section.iter_mut() }))
+        self.iter.next().map(|(name, section)| (name, SectionIterMut { iter: section.values.iter_mut() }))
+    }
+}
+
+/// An owning iterator over the sections of an ini document, created by [`Ini::into_iter()`]
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let conf = Ini::from_string("[a]\nx = 1\n[b]\ny = 2").unwrap();
+///
+/// let names: Vec<String> = conf.into_iter().map(|(name, _)| name).collect();
+/// assert_eq!(names, ["a", "b"]);
+/// ```
+pub struct IniIntoIter {
+    #[doc(hidden)]
+    iter: ordered_hashmap::IntoIter<String, Section>,
+}
+
+impl Iterator for IniIntoIter {
+    type Item = (String, Section);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+impl IntoIterator for Ini {
+    type Item = (String, Section);
+    type IntoIter = IniIntoIter;
+
+    fn into_iter(self) -> IniIntoIter {
+        IniIntoIter { iter: self.document.into_iter() }
+    }
+}
+
+/// An owning iterator over the entries of a section, created by [`Section::into_iter()`]
+///
+/// # Example
+/// ```
+/// # use tini::Section;
+/// let section = Section::new().set("a", 1).set("b", 2);
+///
+/// let pairs: Vec<(String, String)> = section.into_iter().collect();
+/// assert_eq!(pairs, [("a".to_owned(), "1".to_owned()), ("b".to_owned(), "2".to_owned())]);
+/// ```
+pub struct SectionIntoIter {
+    #[doc(hidden)]
+    iter: ordered_hashmap::IntoIter<String, String>,
+}
+
+impl Iterator for SectionIntoIter {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
     }
 }
 
-type Section = OrderedHashMap<String, String>;
+impl IntoIterator for Section {
+    type Item = (String, String);
+    type IntoIter = SectionIntoIter;
+
+    fn into_iter(self) -> SectionIntoIter {
+        SectionIntoIter { iter: self.values.into_iter() }
+    }
+}
 
 /// An iterator over the entries of a section
 pub struct SectionIter<'a> {
@@ -622,7 +3550,7 @@ impl<'a> Iterator for SectionIter<'a> {
 impl<'a> SectionIter<'a> {
     /// Get scalar value of key
     ///
-    /// - output type `T` must implement [FromStr] trait for auto conversion
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
     ///
     /// # Example
     /// ```
@@ -638,9 +3566,9 @@ impl<'a> SectionIter<'a> {
     /// ```
     pub fn get<T>(&'a self, key: &str) -> Option<T>
     where
-        T: FromStr,
+        T: FromIniValue,
     {
-        self.document.get(key).and_then(|x| x.parse().ok())
+        self.document.get(key)
     }
 }
 
@@ -689,30 +3617,289 @@ mod library_test {
     #[test]
     fn empty_key() {
         match Ini::from_string("[a]\nx = 1\n=2") {
-            Err(Error::Parse(ParseError::EmptyKey(index))) => assert_eq!(index, 3),
-            _ => assert!(false),
+            Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::EmptyKey, 3)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let config = ParserConfig::default().duplicate_keys(DuplicateKeyPolicy::Error);
+        match config.parse_str("[a]\nx = 1\n[b]\nx = 2\n[a]\nx = 3") {
+            Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::DuplicateKey, 6)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn duplicate_key_multiline() -> Result<(), Error> {
+        let config = ParserConfig::default().multiline_values(true);
+        let input = "[a]\nx = 1\n  2\nx = 3\n  4";
+        let ini = config.clone().duplicate_keys(DuplicateKeyPolicy::KeepFirst).parse_str(input)?;
+        assert_eq!(ini.get::<String>("a", "x").unwrap(), "1\n2");
+        let ini = config.duplicate_keys(DuplicateKeyPolicy::CollectIntoVec).parse_str(input)?;
+        assert_eq!(ini.get::<String>("a", "x").unwrap(), "1\n2, 3\n4");
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_section() {
+        let config = ParserConfig::default().duplicate_sections(DuplicateSectionPolicy::Error);
+        match config.parse_str("[a]\n[b]\nx = 2\n\n[a]\nx = 3") {
+            Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::DuplicateSection, 5)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn strict_policies() {
+        let input = "[a]\nx = 1\nx = 2\n[a]";
+        let last = ParserConfig::default().duplicate_keys(DuplicateKeyPolicy::KeepLast);
+        assert!(last.clone().strict(true).parse_str(input).is_err());
+        let relaxed = last.strict(true).duplicate_sections(DuplicateSectionPolicy::Merge);
+        assert_eq!(relaxed.parse_str(input).unwrap().get::<u8>("a", "x"), Some(2));
+        let relaxed = ParserConfig::default().duplicate_sections(DuplicateSectionPolicy::Merge).strict(true);
+        let relaxed = relaxed.duplicate_keys(DuplicateKeyPolicy::KeepFirst);
+        assert_eq!(relaxed.parse_str(input).unwrap().get::<u8>("a", "x"), Some(1));
+        assert!(ParserConfig::default().strict(true).strict(false).parse_str(input).is_ok());
+    }
+
+    #[test]
+    fn parse_lossy() {
+        let config = ParserConfig::default().strict(true);
+        let (ini, issues) = config.parse_lossy("x = 0\n[a]\nb = 1\nb = 2\n  c\n[a]\nd = 3\n[e\n");
+        assert_eq!(ini.get::<u8>("a", "b"), Some(1));
+        assert_eq!(ini.get::<u8>("a", "d"), Some(3));
+        assert!(!ini.has_key("", "x"));
+        let errors: Vec<(ParseErrorKind, usize)> =
+            issues.iter().map(|issue| (issue.error.kind(), issue.line)).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (ParseErrorKind::KeyOutsideSection, 1),
+                (ParseErrorKind::DuplicateKey, 4),
+                (ParseErrorKind::IncorrectSyntax, 5),
+                (ParseErrorKind::DuplicateSection, 6),
+                (ParseErrorKind::IncorrectSection, 8),
+            ]
+        );
+        assert_eq!(issues[2].text, "c");
+    }
+
+    #[test]
+    fn try_item() -> Result<(), Error> {
+        for key in ["", " a", "a\nb", "a;b", "[a]", "a=b", "a:b"] {
+            assert!(Ini::new().delimiter(':').section("s").try_item(key, 1).is_err(), "{:?}", key);
+        }
+        for section in ["", "a]", "a#b", "a\r"] {
+            assert!(Ini::new().try_section(section).is_err(), "{:?}", section);
+        }
+        let ini = Ini::new().try_section("s")?.try_item("a b", "x = [y]")?.try_item("c", "")?;
+        let parsed = Ini::from_string(ini.to_string())?;
+        assert_eq!(parsed.get_raw("s", "a b"), Some("x = [y]"));
+        assert_eq!(parsed.get_raw("s", "c"), Some(""));
+        Ok(())
+    }
+
+    #[test]
+    fn comments() {
+        let ini = Ini::new()
+            .comment("generated")
+            .item("version", 1)
+            .section("a")
+            .item("b", 2)
+            .comment("removed")
+            .item("c", 3)
+            .comment("kept")
+            .erase("b")
+            .format(FormatOptions::new().indent(2));
+        assert_eq!(ini.to_string(), "; generated\n  version = 1\n\n[a]\n  ; kept\n  c = 3\n");
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let ini = Ini::new().item("g", 0).section("a").item("b", "1\n2").section("c").item("d", 3);
+        let options = FormatOptions::new().blank_lines(2).trailing_newline(false).line_ending(LineEnding::CrLf);
+        assert_eq!(ini.to_string_with(&options), "g = 0\r\n\r\n\r\n[a]\r\nb = 1\r\n    2\r\n\r\n\r\n[c]\r\nd = 3");
+        assert_eq!(Ini::new().to_string_with(&FormatOptions::new()), "");
+    }
+
+    #[test]
+    fn non_ascii_roundtrip() {
+        let ini = Ini::new().preamble("© 2024").section("größe").item("ключ", "値 \u{1f600}");
+        let output = ini.to_string_with(&FormatOptions::new().escape_non_ascii(true).quoting(QuoteMode::Auto));
+        assert!(output.is_ascii());
+        let parsed = ParserConfig::default().unicode_escapes(true).parse_str(&output).unwrap();
+        assert_eq!(parsed.to_string(), ini.to_string());
+    }
+
+    #[test]
+    fn preamble() {
+        let ini = Ini::from_string("# one\n  ; two\n[a]\n; not preamble\nb = 1").unwrap();
+        assert_eq!(ini.get_preamble(), Some("one\ntwo"));
+        assert!(!ini.is_modified());
+        let ini = Ini::from_string(ini.preamble("three").to_string()).unwrap();
+        assert_eq!(ini.get_preamble(), Some("three"));
+        assert_eq!(ini.preamble("").to_string(), "[a]\nb = 1\n");
+        assert_eq!(Ini::from_string("; only").unwrap().get_preamble(), Some("only"));
+        assert_eq!(Ini::from_string("a = 1\n; late").unwrap().get_preamble(), None);
+    }
+
+    #[test]
+    fn entry_case_insensitive() {
+        let mut ini = Ini::builder().case_insensitive(true).parse_str("[Server]\nPort = 80").unwrap();
+        assert_eq!(ini.entry("SERVER").or_default().entry("PORT").or_insert(8080), "80");
+        ini.entry("server").or_default().entry("Host").and_modify(|v| v.clear()).or_insert("localhost");
+        assert_eq!(ini.get_raw("server", "HOST").unwrap(), "localhost");
+        assert_eq!(ini.remove_section("SERVER").unwrap().get_raw("port"), Some("80"));
+    }
+
+    #[test]
+    fn merge() {
+        let base = Ini::from_string("[a]\nx = 1\ny = 2 ; base").unwrap();
+        let other = Ini::from_string("[a]\ny = 3 ; other\nz = 4 ; new\n[b]\nw = 5").unwrap();
+        let merged = base.merge(other, MergePolicy::KeepExisting).unwrap();
+        assert_eq!(merged.to_string(), "[a]\nx = 1\ny = 2\nz = 4\n\n[b]\nw = 5\n");
+        assert_eq!(merged.inline_comment("a", "y"), Some("base"));
+        assert_eq!(merged.inline_comment("a", "z"), Some("new"));
+
+        let same = Ini::from_string("[a]\nx = 1").unwrap();
+        let merged = merged.merge(same, MergePolicy::Error).unwrap();
+        let conflict = Ini::from_string("[b]\nw = 6").unwrap();
+        match merged.merge(conflict, MergePolicy::Error) {
+            Err(Error::Merge(error)) => assert_eq!(error, MergeError::Conflict("b".to_owned(), "w".to_owned())),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "key `port` is not found")]
+    fn index_missing_key() {
+        let ini = Ini::from_string("[server]\nhost = localhost").unwrap();
+        let _ = &ini["server"]["port"];
+    }
+
+    #[test]
+    fn get_section() {
+        let mut ini = Ini::builder().case_insensitive(true).parse_str("[Logging]\nLevel = debug").unwrap();
+        assert_eq!(ini.get_section("LOGGING").and_then(|s| s.get_raw("level")), Some("debug"));
+        assert!(ini.get_section_mut("other").is_none());
+        assert!(!ini.is_modified());
+        ini.get_section_mut("logging").unwrap().insert("Target", "stderr");
+        assert!(ini.is_modified());
+        assert_eq!(ini.get_raw("logging", "target"), Some("stderr"));
+    }
+
+    #[test]
+    fn vec_separator() {
+        let mut ini = Ini::new().section("a").item_vec_with_sep("list", &["x"], " ").item("empty", "");
+        ini.push_to_vec("a", "list", "y");
+        ini.push_to_vec("a", "empty", 1);
+        assert!(ini.remove_from_vec("a", "list", "x"));
+        assert_eq!(ini.get_raw("a", "list").unwrap(), "y");
+        assert_eq!(ini.get_raw("a", "empty").unwrap(), "1");
+
+        let ini = ini.section("a").item("list", 1);
+        assert_eq!(ini.vec_separator("a", "list"), None);
+        let ini = ini.section("a").item_vec("list", &[1]).erase("list");
+        assert_eq!(ini.vec_separator("a", "list"), None);
+    }
+
+    #[test]
+    fn save_if_changed() {
+        let path = std::env::temp_dir().join(format!("tini-save-{}.ini", std::process::id()));
+        let mut ini = Ini::new().section("a").item("b", 1);
+        assert!(ini.is_modified());
+        assert!(ini.save_if_changed(&path).unwrap());
+        assert!(!ini.is_modified());
+        assert!(!ini.save_if_changed(&path).unwrap());
+
+        let mut ini = Ini::from_file(&path).unwrap().section("a").item("b", 1);
+        assert!(ini.is_modified());
+        assert!(!ini.save_if_changed(&path).unwrap());
+        let mut ini = ini.section("a").item("b", 2);
+        assert!(ini.save_if_changed(&path).unwrap());
+        assert_eq!(Ini::from_file(&path).unwrap().get::<u8>("a", "b"), Some(2));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn limits() {
+        let config = ParserConfig::default().max_line_length(6).max_sections(1).max_keys(2);
+        assert!(config.parse_str("[a]\nb = 1\n[a]\nc = 2").is_ok());
+        let cases = [
+            ("[a]\nb = 1\n[b]", (ParseErrorKind::TooManySections, 3)),
+            ("[a]\nb = 1\nc = 2\nd = 3", (ParseErrorKind::TooManyKeys, 4)),
+            ("[a]\nb = ъ\nb =  ъ", (ParseErrorKind::LineTooLong, 3)),
+        ];
+        for (input, expected) in cases {
+            match config.parse_str(input) {
+                Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), expected),
+                _ => unreachable!(),
+            }
+        }
+        let (ini, issues) = config.parse_lossy("[a]\nb = 1\nlong line\nc = 2");
+        assert_eq!(issues.len(), 1);
+        assert!(!ini.has_key("a", "c"));
+        let (_, issues) = config.clone().max_input_size(4).parse_lossy("[a]\nb = 1");
+        assert_eq!(
+            (issues[0].error.kind(), issues[0].line, issues[0].error.offset()),
+            (ParseErrorKind::InputTooLarge, 2, 4)
+        );
+    }
+
+    #[test]
+    fn array_keys() -> Result<(), Error> {
+        let config = ParserConfig::default().array_keys(true).duplicate_keys(DuplicateKeyPolicy::Error);
+        let ini = config.parse_str("[a]\nx[] = 1\nx [] = 2\ny[] = 3\nx[] = 4")?;
+        assert_eq!(ini.get_vec::<u8>("a", "x"), Some(vec![1, 2, 4]));
+        assert_eq!(ini.get_vec::<u8>("a", "y"), Some(vec![3]));
+        assert!(ParserConfig::default().duplicate_keys(DuplicateKeyPolicy::Error).parse_str("[a]\nx[] = 1").is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn interpolation_errors() -> Result<(), Error> {
+        let cases = [
+            ("[a]\nb = ${b}", "Cyclic reference to key `b` in section `a`"),
+            ("[a]\nb = ${c:d}", "Reference to missing key `d` in section `c`"),
+            ("[a]\nb = ${c", "Incorrect reference syntax in key `b` in section `a`"),
+            ("[a]\nb = ${}", "Incorrect reference syntax in key `b` in section `a`"),
+        ];
+        for (input, message) in &cases {
+            match Ini::from_string(*input)?.interpolated() {
+                Err(e @ Error::Interpolation(_)) => assert_eq!(e.to_string(), *message),
+                _ => unreachable!(),
+            }
         }
+        Ok(())
     }
 
     #[test]
     fn invalid_section() {
         match Ini::from_string("[a]\nx = 1\ny = 2\n[b") {
-            Err(Error::Parse(ParseError::IncorrectSection(index))) => assert_eq!(index, 4),
-            _ => assert!(false),
+            Err(Error::Parse(error)) => assert_eq!((error.kind(), error.line()), (ParseErrorKind::IncorrectSection, 4)),
+            _ => unreachable!(),
         }
     }
 
     #[test]
     fn invalid_syntax() {
         match Ini::from_string("[a]\n\t- b") {
-            Err(Error::Parse(ParseError::IncorrectSyntax(index))) => assert_eq!(index, 2),
-            _ => assert!(false),
+            Err(Error::Parse(error)) => {
+                assert_eq!(
+                    (error.kind(), error.line(), error.column(), error.offset()),
+                    (ParseErrorKind::IncorrectSyntax, 2, 2, 5)
+                );
+                assert_eq!(error.to_string(), "Incorrect syntax at line 2, column 2\n\t- b\n\t^");
+            }
+            _ => unreachable!(),
         }
     }
 
     #[test]
     fn bad_cast() {
-        let ini = Ini::new().section("one").item("a", 3.14);
+        let ini = Ini::new().section("one").item("a", 2.5);
         let a: Option<u32> = ini.get("one", "a");
         assert_eq!(a, None);
     }
@@ -741,6 +3928,62 @@ mod library_test {
         Ok(())
     }
 
+    #[test]
+    fn inline_comment_erased_with_key() -> Result<(), Error> {
+        let mut ini = Ini::from_string("[a]\nb = 1 # note\nc = 2 ; other")?;
+        assert_eq!(ini.inline_comment("a", "b"), Some("note"));
+        ini = ini.section("a").erase("b");
+        assert_eq!(ini.inline_comment("a", "b"), None);
+        assert_eq!(ini.inline_comment("a", "c"), Some("other"));
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_roundtrip() -> Result<(), Error> {
+        let value = "a  b c d e f g h i j k l m n o p";
+        let ini = Ini::new().wrap_values(12).section("s").item("key", value);
+        assert!(ini.to_string().lines().all(|line| line.len() <= 12));
+        let parsed = Ini::builder().line_continuation(true).parse_str(&ini.to_string())?;
+        assert_eq!(parsed.get::<String>("s", "key").unwrap(), value);
+
+        let ini = Ini::new().wrap_values(30).section("s").item("ключ", "слово слово слово слово слово");
+        assert_eq!(ini.to_string(), "[s]\nключ = слово слово слово \\\n    слово слово\n");
+        Ok(())
+    }
+
+    #[test]
+    fn multiline_roundtrip() -> Result<(), Error> {
+        let config = ParserConfig::default().multiline_values(true);
+        let ini = config.parse_str("[a]\nb =\n  one ; first\n; comment\n\ttwo\n\n[c]\n  d = 1\n  e = 2")?;
+        assert_eq!(ini.get::<String>("a", "b").unwrap(), "one\ntwo");
+        assert_eq!(ini.get::<u8>("c", "e"), Some(2));
+        assert_eq!(ini.to_string(), "[a]\nb = one\n    two\n\n[c]\nd = 1\ne = 2\n");
+        let parsed = config.parse_str(&ini.to_string())?;
+        assert_eq!(parsed.get::<String>("a", "b").unwrap(), "one\ntwo");
+        Ok(())
+    }
+
+    #[test]
+    fn global_keys() -> Result<(), Error> {
+        let ini = Ini::from_string("a = 1\n[b]\nc = 2")?;
+        assert_eq!(ini.get::<u8>("", "a"), Some(1));
+        assert_eq!(ini.to_string(), "a = 1\n\n[b]\nc = 2\n");
+        let ini = Ini::new().section("b").item("c", 2).section("").item("a", 1);
+        assert_eq!(ini.to_string(), "a = 1\n\n[b]\nc = 2\n");
+        Ok(())
+    }
+
+    #[test]
+    fn bom_roundtrip() -> Result<(), Error> {
+        let mut reader = io::BufReader::new("\u{feff}[a]\nb = 1".as_bytes());
+        let ini = Ini::from_reader(&mut reader)?;
+        assert_eq!(ini.section_iter("a").count(), 1);
+        let mut output = Vec::new();
+        ini.to_writer(&mut output)?;
+        assert_eq!(output, "\u{feff}[a]\nb = 1\n".as_bytes());
+        Ok(())
+    }
+
     #[test]
     fn ordering_iter() -> Result<(), Error> {
         let ini = Ini::from_string("[a]\nc = 1\nb = 2\na = 3")?;
@@ -748,4 +3991,290 @@ mod library_test {
         assert_eq!(["c", "b", "a"], keys[..]);
         Ok(())
     }
+
+    #[test]
+    fn subsections() {
+        let input = "[A.b.c.d]\nx = 1\n[a.B]\ny = 2\n[a.e]\n[other.a.f]";
+        let names = |ini: &Ini, section: &str| -> Vec<(String, bool)> {
+            ini.subsections(section).map(|(name, s)| (name.to_owned(), s.is_some())).collect()
+        };
+        let ini = Ini::builder().case_insensitive(true).parse_str(input).unwrap();
+        assert!(!ini.has_section("a"));
+        assert_eq!(names(&ini, "A"), [("a.b".to_owned(), true), ("a.e".to_owned(), true)]);
+        assert_eq!(names(&ini, "a.b"), [("a.b.c".to_owned(), false)]);
+        assert_eq!(names(&ini, "a.b.c"), [("a.b.c.d".to_owned(), true)]);
+        assert!(names(&ini, "a.b.c.d").is_empty());
+        assert!(names(&ini, "a.e").is_empty());
+        assert!(names(&ini, "missing").is_empty());
+        assert_eq!(ini.subsections("a.b.c").next().and_then(|(_, s)| s.and_then(|s| s.get::<u8>("x"))), Some(1));
+
+        let ini = Ini::from_string(input).unwrap();
+        assert_eq!(names(&ini, "a"), [("a.B".to_owned(), true), ("a.e".to_owned(), true)]);
+        assert_eq!(names(&ini, "A"), [("A.b".to_owned(), false)]);
+    }
+
+    #[test]
+    fn merge_modified() {
+        let base = || Ini::from_string("[a]\nx = 1").unwrap();
+        assert!(!base().merge(Ini::new(), MergePolicy::OverwriteExisting).unwrap().is_modified());
+        let same = Ini::from_string("[a]\nx = 1").unwrap();
+        assert!(!base().merge(same, MergePolicy::OverwriteExisting).unwrap().is_modified());
+        let kept = Ini::from_string("[a]\nx = 2").unwrap();
+        assert!(!base().merge(kept, MergePolicy::KeepExisting).unwrap().is_modified());
+
+        let changed = Ini::from_string("[a]\nx = 2").unwrap();
+        assert!(base().merge(changed, MergePolicy::OverwriteExisting).unwrap().is_modified());
+        let added = Ini::from_string("[a]\ny = 2").unwrap();
+        assert!(base().merge(added, MergePolicy::KeepExisting).unwrap().is_modified());
+        let section = Ini::from_string("[b]").unwrap();
+        assert!(base().merge(section, MergePolicy::Error).unwrap().is_modified());
+    }
+
+    #[test]
+    fn env_overrides() {
+        let vars = [
+            ("APP_A__X", "2"),
+            ("APP_B__Y", "3"),
+            ("APP_C", "4"),
+            ("APP_SERVER__PORT", "9000"),
+            ("APP__Z", "5"),
+            ("OTHER_A__X", "6"),
+        ];
+        let vars = vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        let mut ini = Ini::from_string("[Server]\nhost = localhost\nPort = 80\n[a]\nX = 1").unwrap();
+        ini.apply_env_vars("APP", vars);
+        assert_eq!(ini.to_string(), "[Server]\nhost = localhost\nPort = 9000\n\n[a]\nX = 2\n\n[b]\ny = 3\n");
+        assert!(ini.is_modified());
+    }
+
+    #[test]
+    fn flatten_roundtrip() {
+        let ini = Ini::builder().global_section("general").parse_str("top = 1\n[a]\nb = 2\n[c.d]\ne = 3").unwrap();
+        let flat = ini.flatten();
+        assert_eq!(
+            flat,
+            [
+                ("top".to_owned(), "1".to_owned()),
+                ("a.b".to_owned(), "2".to_owned()),
+                ("c.d.e".to_owned(), "3".to_owned())
+            ]
+        );
+        let back = Ini::from_flat(flat.clone());
+        assert_eq!(back.to_string(), "top = 1\n\n[a]\nb = 2\n\n[c.d]\ne = 3\n");
+        assert_eq!(back.flatten(), flat);
+        assert_eq!(Ini::from_flat([(".top", 1)]).get_raw("", "top"), Some("1"));
+    }
+
+    #[test]
+    fn std_maps() {
+        let ini = Ini::from_string("[b]\nz = 3\n[a]\ny = 2\nx = 1\n[empty]").unwrap();
+        let map = HashMap::from(ini.clone());
+        assert_eq!(map["b"]["z"], "3");
+        assert!(map["empty"].is_empty());
+        let back = Ini::from(map);
+        assert_eq!(back, ini);
+        assert_eq!(back.section_names().collect::<Vec<_>>(), ["a", "b", "empty"]);
+    }
+
+    #[test]
+    fn try_from_path() {
+        let path = std::env::temp_dir().join(format!("tini-try-from-{}.ini", std::process::id()));
+        std::fs::write(&path, "[a]\nb = 1").unwrap();
+        let ini = Ini::try_from(path.as_path());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ini.unwrap().get::<u8>("a", "b"), Some(1));
+        assert!(matches!(Ini::try_from(path.as_path()), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn file_path() {
+        let dir = std::env::temp_dir().join(format!("tini-file-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.ini");
+        std::fs::write(&path, "[a]\nlog = logs/app.log\nhome = ~/app\nother = ~user/app").unwrap();
+        let ini = Ini::from_file(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(ini.source_path(), Some(path.as_path()));
+        assert_eq!(ini.get_file_path("a", "log", None), Some(dir.join("logs/app.log")));
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(ini.get_file_path("a", "home", None), Some(Path::new(&home).join("app")));
+        }
+        assert_eq!(ini.get_file_path("a", "other", None), Some(dir.join("~user/app")));
+    }
+
+    #[test]
+    fn append_namespaced() {
+        let other = Ini::from_string("top = 1\n[x]\na = 1\n[p.x]\nb = 2 ; two").unwrap();
+        let ini = Ini::new().append_namespaced(other, "p");
+        assert_eq!(ini.section_names().collect::<Vec<_>>(), ["p", "p.x", "p.p.x"]);
+        assert_eq!(ini.get_raw("p", "top"), Some("1"));
+        assert_eq!(ini.get_raw("p.x", "a"), Some("1"));
+        assert_eq!(ini.get_raw("p.p.x", "b"), Some("2"));
+        assert_eq!(ini.inline_comment("p.p.x", "b"), Some("two"));
+    }
+
+    #[test]
+    fn append_namespaced_global_section() {
+        let other = Ini::builder().global_section("general").parse_str("top = 1\n[general]\nmore = 2").unwrap();
+        let base = Ini::builder().case_insensitive(true).parse_str("[db]\nhost = x").unwrap();
+        let ini = base.append_namespaced(other, "DB");
+        assert_eq!(ini.section_names().collect::<Vec<_>>(), ["db"]);
+        assert_eq!(ini.get_raw("db", "top"), Some("1"));
+        assert_eq!(ini.get_raw("db", "more"), Some("2"));
+        assert_eq!(ini.get_raw("db", "host"), Some("x"));
+        assert!(!ini.has_section("db.general"));
+    }
+
+    #[test]
+    fn set_section_from_comments() {
+        struct Host(&'static str);
+
+        impl ToSection for Host {
+            fn to_section(&self) -> Section {
+                Section::new().set("host", self.0)
+            }
+        }
+
+        let mut ini = Ini::from_string("[db]\nhost = a ; primary\nport = 1 ; old port").unwrap().section("db");
+        ini = ini.item("host", "a").comment("about host").item("port", 1).comment("about port");
+        ini.set_section_from("db", &Host("b"));
+        assert_eq!(ini.inline_comment("db", "host"), Some("primary"));
+        assert_eq!(ini.inline_comment("db", "port"), None);
+        ini = ini.section("db").item("port", 2);
+        assert_eq!(ini.to_string(), "[db]\n; about host\nhost = b\nport = 2\n");
+    }
+
+    #[test]
+    fn custom_value() {
+        #[derive(Debug, PartialEq)]
+        struct Flag(bool);
+
+        impl FromIniValue for Flag {
+            fn parse_value(value: &str) -> Option<Self> {
+                match value {
+                    "on" => Some(Flag(true)),
+                    "off" => Some(Flag(false)),
+                    _ => None,
+                }
+            }
+        }
+
+        impl ToIniValue for Flag {
+            fn format_value(&self) -> String {
+                (if self.0 { "on" } else { "off" }).to_owned()
+            }
+        }
+
+        let mut ini = Ini::new().section("a").item("x", Flag(true)).item_vec("v", &[Flag(false), Flag(true)]);
+        ini.set("a", "y", Flag(false));
+        ini.set("a", "z", "maybe");
+        assert_eq!(ini.to_string(), "[a]\nx = on\nv = off, on\ny = off\nz = maybe\n");
+        assert_eq!(ini.get("a", "x"), Some(Flag(true)));
+        assert_eq!(ini.get("a", "y"), Some(Flag(false)));
+        assert_eq!(ini.get::<Flag>("a", "z"), None);
+        assert_eq!(ini.get_vec("a", "v"), Some(vec![Flag(false), Flag(true)]));
+        assert_eq!(ini.try_get::<Flag>("a", "x"), Ok(Flag(true)));
+        assert!(matches!(ini.try_get::<Flag>("a", "z"), Err(GetError::ParseFailed { .. })));
+        assert_eq!(ini.get_section("a").and_then(|s| s.get("y")), Some(Flag(false)));
+        assert_eq!(ini.get_or_insert_with("a", "w", || Flag(true)), Flag(true));
+        assert_eq!(ini.get_raw("a", "w"), Some("on"));
+        assert_eq!(ini.get::<std::path::PathBuf>("a", "z"), Some("maybe".into()));
+    }
+
+    #[test]
+    fn default_section() {
+        let input = "[Default]\nUser = admin\nport = 22\n[web]\nport = 80";
+        let ini = Ini::builder().case_insensitive(true).default_section("DEFAULT").parse_str(input).unwrap();
+        assert_eq!(ini.get_raw("WEB", "user"), Some("admin"));
+        assert_eq!(ini.get::<u16>("web", "port"), Some(80));
+        assert!(ini.has_key("web", "user"));
+        assert_eq!(ini.get_section("web").and_then(|s| s.get_raw("user")), None);
+        assert_eq!(ini.get_raw("other", "user"), None);
+        assert_eq!(ini.try_get::<u16>("web", "port"), Ok(80));
+        assert_eq!(ini.try_get::<String>("web", "user"), Ok("admin".to_owned()));
+        assert_eq!(ini.try_get_vec::<String>("web", "user"), Ok(vec!["admin".to_owned()]));
+        assert_eq!(ini.try_get::<u16>("web", "other"), Err(GetError::MissingKey));
+        assert_eq!(ini.try_get::<u16>("other", "port"), Err(GetError::MissingSection));
+
+        struct Login(String, u16);
+        impl FromSection for Login {
+            fn from_section(section: &Section) -> Result<Self, MappingError> {
+                let user = section.try_get("user").map_err(|e| MappingError::key("", "user", e))?;
+                let port = section.try_get("port").map_err(|e| MappingError::key("", "port", e))?;
+                Ok(Login(user, port))
+            }
+        }
+        let login: Login = ini.get_section_as("web").unwrap();
+        assert_eq!((login.0.as_str(), login.1), ("admin", 80));
+        let login: Login = ini.get_section_as("default").unwrap();
+        assert_eq!((login.0.as_str(), login.1), ("admin", 22));
+
+        let ini = Ini::from_string(input).unwrap();
+        assert_eq!(ini.get_raw("web", "User"), None);
+        assert_eq!(ini.try_get::<String>("web", "User"), Err(GetError::MissingKey));
+    }
+
+    #[test]
+    fn select() {
+        let ini = Ini::builder().case_insensitive(true).parse_str("[A]\nx = 1\n[b]\ny = 2\n[c]\nz = 3").unwrap();
+        let selected = ini.select(&["C".to_owned(), "a".to_owned()]);
+        assert_eq!(selected.section_names().collect::<Vec<_>>(), ["a", "c"]);
+        assert!(!selected.is_modified());
+        assert!(ini.select::<&str>(&[]).is_empty());
+        assert_eq!(ini.len(), 3);
+    }
+
+    #[test]
+    fn parse_hooks() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let config = Ini::builder()
+            .on_section(|name| if name == "bad" { SectionAction::Reject } else { SectionAction::Keep })
+            .on_entry(move |entry| {
+                log.lock().unwrap().push((entry.line, entry.key.to_owned(), entry.duplicate));
+                if entry.key == "bad" {
+                    EntryAction::Reject
+                } else {
+                    EntryAction::Keep
+                }
+            });
+        let (ini, issues) = config.parse_lossy("[a]\nx = 1\nx = 2\nbad = 3\n[bad]\ny = 4");
+        assert_eq!(ini.to_string(), "[a]\nx = 2\n");
+        assert_eq!(
+            issues.iter().map(|issue| (issue.line, issue.error.kind())).collect::<Vec<_>>(),
+            [(4, ParseErrorKind::Rejected), (5, ParseErrorKind::Rejected)]
+        );
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [(2, "x".to_owned(), false), (3, "x".to_owned(), true), (4, "bad".to_owned(), false)]
+        );
+    }
+
+    #[test]
+    fn apply_overrides() {
+        let mut ini = Ini::from_string("top = 1\n[a]\nb = 2").unwrap();
+        ini.apply_overrides(vec![".top=3".to_owned(), "sub.a.b = x=y".to_owned()]).unwrap();
+        assert_eq!(ini.to_string(), "top = 3\n\n[a]\nb = 2\n\n[sub.a]\nb = x=y\n");
+        for malformed in ["a=1", "a. =1", "=1"] {
+            let error = ini.apply_overrides(["x.y=1", malformed]).unwrap_err();
+            assert!(matches!(error, Error::Override(OverrideError::InvalidPath(1, text)) if text == malformed));
+        }
+    }
+
+    #[test]
+    fn apply_overrides_global_section() {
+        let mut ini = Ini::builder().global_section("general").parse_str("top = 1\n[a]\nb = 2").unwrap();
+        ini.apply_overrides([".top=5", ".new = x"]).unwrap();
+        assert_eq!(ini.to_string(), "top = 5\nnew = x\n\n[a]\nb = 2\n");
+        assert_eq!(Ini::from_string(ini.to_string()).unwrap().get_raw("", "top"), Some("5"));
+        assert!(!ini.has_section(""));
+    }
+
+    #[test]
+    fn apply_overrides_error_keeps_document() {
+        let mut ini = Ini::from_string("[a]\nb = 2").unwrap();
+        assert!(ini.apply_overrides(["a.b=3", "c.d=4", "broken"]).is_err());
+        assert_eq!(ini.to_string(), "[a]\nb = 2\n");
+        assert!(!ini.is_modified());
+    }
 }
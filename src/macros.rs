@@ -0,0 +1,75 @@
+//! Macros module
+//!
+//! Contains [ini!](crate::ini!) macro to construct [Ini](crate::Ini) in place
+
+/// Construct [Ini](crate::Ini) from sections with key-value pairs
+///
+/// Section names and keys may be identifiers or string literals. Values are any expressions
+/// supporting [Display](std::fmt::Display), values in square brackets are added as vectors
+/// with [`item_vec()`](crate::Ini::item_vec).
+///
+/// # Example
+/// ```
+/// # use tini::ini;
+/// let conf = ini! {
+///     server {
+///         host: "localhost",
+///         port: 8000 + 80,
+///         methods: ["GET", "POST"],
+///     },
+///     "log files" {
+///         "error.log": true,
+///     },
+/// };
+///
+/// assert_eq!(conf.to_string(), [
+///     "[server]",
+///     "host = localhost",
+///     "port = 8080",
+///     "methods = GET, POST",
+///     "",
+///     "[log files]",
+///     "error.log = true",
+///     "",
+/// ].join("\n"));
+/// ```
+#[macro_export]
+macro_rules! ini {
+    (@name $name:ident) => {
+        stringify!($name)
+    };
+    (@name $name:literal) => {
+        $name
+    };
+    (@items $ini:expr;) => {
+        $ini
+    };
+    (@items $ini:expr; $key:tt : [$($item:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::ini!(@items $ini.item_vec($crate::ini!(@name $key), &[$($item),*]); $($($rest)*)?)
+    };
+    (@items $ini:expr; $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::ini!(@items $ini.item($crate::ini!(@name $key), $value); $($($rest)*)?)
+    };
+    ($($section:tt { $($items:tt)* }),* $(,)?) => {{
+        let ini = $crate::Ini::new();
+        $(let ini = $crate::ini!(@items ini.section($crate::ini!(@name $section)); $($items)*);)*
+        ini
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn empty() {
+        assert_eq!(ini! {}.to_string(), "");
+        assert_eq!(ini! { a {} }.to_string(), "");
+    }
+
+    #[test]
+    fn variables() {
+        let port = 80;
+        let hosts = ["a", "b"];
+        let conf = ini! { net { port: port, hosts: hosts.join(" ") } };
+        assert_eq!(conf.to_string(), "[net]\nport = 80\nhosts = a b\n");
+    }
+}
@@ -0,0 +1,113 @@
+//! Mapping module
+//!
+//! Contains [FromIni], [ToIni], [FromSection] and [ToSection] traits to convert documents to structures and back.
+//! With `derive` feature enabled they can be derived for structures with named fields, see
+//! [tini-derive](https://docs.rs/tini-derive) for supported attributes.
+use crate::error::{GetError, MappingError};
+use crate::{FromIniValue, Ini, Section, ToIniValue};
+
+/// Structure which can be built from [Section]
+///
+/// # Example
+/// ```
+/// # use tini::{FromIniValue, FromSection, GetError, MappingError, Section};
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// fn value<T: FromIniValue>(section: &Section, key: &str) -> Result<T, MappingError> {
+///     section.try_get(key).map_err(|error| MappingError::key("", key, error))
+/// }
+///
+/// impl FromSection for Server {
+///     fn from_section(section: &Section) -> Result<Self, MappingError> {
+///         Ok(Server { host: value(section, "host")?, port: value(section, "port")? })
+///     }
+/// }
+///
+/// let server = Server::from_section(&Section::new().set("host", "localhost").set("port", 8080)).unwrap();
+/// assert_eq!((server.host.as_str(), server.port), ("localhost", 8080));
+///
+/// let error = Server::from_section(&Section::new().set("host", "localhost")).err().unwrap();
+/// assert_eq!(error.key.as_deref(), Some("port"));
+/// assert_eq!(error.error, GetError::MissingKey);
+/// ```
+pub trait FromSection: Sized {
+    /// Build structure from keys of `section`
+    ///
+    /// # Errors
+    /// [MappingError] with empty section name, it is filled by [FromIni] implementations
+    fn from_section(section: &Section) -> Result<Self, MappingError>;
+}
+
+/// Structure which can be written to [Section]
+pub trait ToSection {
+    /// Write fields of structure as keys of new section
+    fn to_section(&self) -> Section;
+}
+
+/// Structure which can be built from [Ini]
+pub trait FromIni: Sized {
+    /// Build structure from sections of `ini`
+    ///
+    /// # Errors
+    /// [MappingError] pointing to the absent or invalid section or key
+    fn from_ini(ini: &Ini) -> Result<Self, MappingError>;
+}
+
+/// Structure which can be written to [Ini]
+pub trait ToIni {
+    /// Write fields of structure as sections of new document
+    fn to_ini(&self) -> Ini;
+}
+
+/// Helpers used by code generated with `derive` feature, not a public API
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub fn value<T: FromIniValue>(section: &Section, key: &str) -> Result<Option<T>, MappingError> {
+        match section.get_raw(key) {
+            None => Ok(None),
+            Some(raw) => T::parse_value(raw).map(Some).ok_or_else(|| parse_failed::<T>(key, raw)),
+        }
+    }
+
+    pub fn vec<T: FromIniValue>(section: &Section, key: &str) -> Result<Option<Vec<T>>, MappingError> {
+        match section.try_get_vec(key) {
+            Ok(values) => Ok(Some(values)),
+            Err(GetError::MissingKey) => Ok(None),
+            Err(GetError::Empty) => Ok(Some(Vec::new())),
+            Err(error) => Err(MappingError::key("", key, error)),
+        }
+    }
+
+    pub fn section<T: FromSection>(ini: &Ini, name: &str) -> Result<Option<T>, MappingError> {
+        match ini.get_section(name) {
+            None => Ok(None),
+            Some(section) => T::from_section(section).map(Some).map_err(|error| error.in_section(name)),
+        }
+    }
+
+    pub fn missing_key(key: &str) -> MappingError {
+        MappingError::key("", key, GetError::MissingKey)
+    }
+
+    pub fn missing_section(name: &str) -> MappingError {
+        MappingError::section(name, GetError::MissingSection)
+    }
+
+    pub fn join<T: ToIniValue>(values: &[T]) -> String {
+        values.iter().map(|v| v.format_value()).collect::<Vec<_>>().join(", ")
+    }
+
+    pub fn insert_section(ini: &mut Ini, name: &str, section: Section) {
+        *ini.section_mut(name.to_owned()) = section;
+    }
+
+    fn parse_failed<T>(key: &str, value: &str) -> MappingError {
+        let error = GetError::ParseFailed { value: value.to_owned(), type_name: std::any::type_name::<T>() };
+        MappingError::key("", key, error)
+    }
+}
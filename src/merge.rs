@@ -0,0 +1,15 @@
+//! Merge module
+//!
+//! Contains [MergePolicy] enum to control [`Ini::merge()`](crate::Ini::merge)
+
+/// Action on key which is present in both merged documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// replace value with the one from merged document
+    #[default]
+    OverwriteExisting,
+    /// keep value of the original document
+    KeepExisting,
+    /// stop with [MergeError::Conflict](crate::MergeError::Conflict)
+    Error,
+}
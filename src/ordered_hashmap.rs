@@ -12,7 +12,7 @@ use std::iter::IntoIterator;
 
 /// Ordered hashmap built on top of std::collections::HashMap
 /// Keys are stored in the field `keys` in the order they were added
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OrderedHashMap<K, V> {
     #[doc(hidden)]
     base: HashMap<K, V>,
@@ -34,6 +34,30 @@ where
         OrderedHashMap { base: HashMap::<K, V>::new(), keys: Vec::<K>::new() }
     }
 
+    /// Creates an empty `OrderedHashMap` with space for at least `capacity` elements.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map: OrderedHashMap<&str, i32> = OrderedHashMap::with_capacity(10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> OrderedHashMap<K, V> {
+        OrderedHashMap { base: HashMap::with_capacity(capacity), keys: Vec::with_capacity(capacity) }
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map: OrderedHashMap<&str, i32> = OrderedHashMap::new();
+    /// map.reserve(10);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.base.reserve(additional);
+        self.keys.reserve(additional);
+    }
+
     /// Returns a reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -116,6 +140,25 @@ where
         self.base.insert(k, v)
     }
 
+    /// Inserts a key-value pair at `index` in iteration order, or at the end if `index` is out of bounds.
+    /// Present key is moved to `index` and its old value is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_at(0, "b", 2);
+    /// assert_eq!(map.keys().collect::<Vec<_>>(), [&"b", &"a"]);
+    /// ```
+    pub fn insert_at(&mut self, index: usize, k: K, v: V) -> Option<V> {
+        if let Some(old) = self.position(&k) {
+            self.keys.remove(old);
+        }
+        self.keys.insert(index.min(self.keys.len()), k.clone());
+        self.base.insert(k, v)
+    }
+
     /// Removes a key from the map, returning the value at the key if the key
     /// was previously in the map.
     ///
@@ -134,13 +177,111 @@ where
     {
         match self.keys.iter().position(|x| x == k) {
             Some(index) => {
-                self.keys.swap_remove(index);
+                self.keys.remove(index);
                 self.base.remove(k)
             }
             None => None,
         }
     }
 
+    /// Replaces key `old` with `new` keeping its position and value.
+    /// Returns `false` if `old` is absent or `new` is already present.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert!(map.rename_key(&"a", "c"));
+    /// assert_eq!(map.keys().collect::<Vec<_>>(), [&"c", &"b"]);
+    /// ```
+    pub fn rename_key<Q>(&mut self, old: &Q, new: K) -> bool
+    where
+        K: Borrow<Q> + PartialEq<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.base.contains_key::<K>(&new) {
+            return false;
+        }
+        match self.keys.iter().position(|x| x == old) {
+            Some(index) => {
+                if let Some(value) = self.base.remove(old) {
+                    self.base.insert(new.clone(), value);
+                }
+                self.keys[index] = new;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns position of key in iteration order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.position(&"b"), Some(1));
+    /// assert_eq!(map.position(&"c"), None);
+    /// ```
+    pub fn position<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: PartialEq<Q>,
+        Q: ?Sized,
+    {
+        self.keys.iter().position(|x| x == k)
+    }
+
+    /// Moves key to `index` in iteration order, shifting keys in between.
+    /// Key is moved to the end if `index` is out of bounds.
+    /// Returns `false` if key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// assert!(map.move_key(&"c", 0));
+    /// assert_eq!(map.keys().collect::<Vec<_>>(), [&"c", &"a", &"b"]);
+    /// ```
+    pub fn move_key<Q>(&mut self, k: &Q, index: usize) -> bool
+    where
+        K: PartialEq<Q>,
+        Q: ?Sized,
+    {
+        match self.position(k) {
+            Some(old) => {
+                let key = self.keys.remove(old);
+                self.keys.insert(index.min(self.keys.len()), key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sorts keys, so that iteration visits them in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("b", 1);
+    /// map.insert("a", 2);
+    /// map.sort_keys();
+    /// assert_eq!(map.keys().collect::<Vec<_>>(), [&"a", &"b"]);
+    /// ```
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.keys.sort();
+    }
+
     /// An iterator visiting all key-value pairs in the order they were added.
     /// The iterator element type is `(&'a K, &'a V)`.
     ///
@@ -200,7 +341,7 @@ where
     ///     println!("{}", key);
     /// }
     /// ```
-    pub fn keys(&self) -> std::slice::Iter<K> {
+    pub fn keys(&self) -> std::slice::Iter<'_, K> {
         self.keys.iter()
     }
 
@@ -300,7 +441,7 @@ where
     type Item = (&'a K, &'a V);
     fn next(&mut self) -> Option<Self::Item> {
         match self.keys_iterator.next() {
-            Some(k) => self.base.get_key_value(&k),
+            Some(k) => self.base.get_key_value(k),
             None => None,
         }
     }
@@ -352,4 +493,34 @@ mod library_test {
         assert_eq!(map.get("a"), Some(&1));
         assert_eq!(map.get("b"), None);
     }
+
+    #[test]
+    fn rename_key() {
+        let mut map: OrderedHashMap<_, _> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        assert!(map.rename_key(&"b", "d"));
+        assert!(!map.rename_key(&"a", "c"));
+        assert!(!map.rename_key(&"b", "e"));
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"a", &1), (&"d", &2), (&"c", &3)]);
+    }
+
+    #[test]
+    fn move_key() {
+        let mut map: OrderedHashMap<_, _> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        assert!(map.move_key(&"a", 1));
+        assert_eq!(map.keys().collect::<Vec<_>>(), [&"b", &"a", &"c"]);
+        assert!(map.move_key(&"b", 10));
+        assert_eq!(map.keys().collect::<Vec<_>>(), [&"a", &"c", &"b"]);
+        assert!(!map.move_key(&"d", 0));
+        assert_eq!(map.position(&"c"), Some(1));
+        assert_eq!(map.insert_at(0, "c", 4), Some(3));
+        assert_eq!(map.insert_at(1, "d", 5), None);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"c", &4), (&"d", &5), (&"a", &1), (&"b", &2)]);
+    }
+
+    #[test]
+    fn remove_keeps_order() {
+        let mut map: OrderedHashMap<_, _> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        assert_eq!(map.remove(&"a"), Some(1));
+        assert_eq!(map.keys().collect::<Vec<_>>(), [&"b", &"c"]);
+    }
 }
@@ -1,55 +1,196 @@
 //! Parser module
 //!
-//! Contains `parse_line` routine to parse single line of ini file
-//! and `Parsed` enum for parsing result
-use crate::error::ParseError;
+//! Contains `parse_line` routine to parse single line of ini file,
+//! `Parsed` enum for parsing result and `Lines` iterator over logical lines
+use crate::config::ParserConfig;
+use crate::error::{ParseError, ParseErrorKind};
+use crate::escape::{closing_quote, quote, unescape_unicode, unquote};
+use std::borrow::Cow;
+use std::iter::Enumerate;
+use std::str::SplitInclusive;
 
 /// Enum for storing one of 4 possible `parse_line` results
 #[derive(Debug)]
 pub enum Parsed {
     /// empty line
     Empty,
-    /// [section]
-    Section(String),
-    /// item = value
-    Value(String, String),
+    /// [section] ; comment
+    Section(String, Option<String>),
+    /// item = value ; comment
+    Value(String, String, Option<String>),
 }
 
-/// parse single line of ini file
-pub fn parse_line(line: &str, index: usize) -> Result<Parsed, ParseError> {
-    let content = match line.split(&[';', '#'][..]).next() {
-        Some(value) => value.trim(),
-        None => return Ok(Parsed::Empty),
+/// Iterator over logical lines of input yielding line number, byte offset of line start and line content
+///
+/// Lines ended with backslash are joined with the next one if line continuation is enabled.
+pub struct Lines<'a> {
+    lines: Enumerate<SplitInclusive<'a, char>>,
+    offset: usize,
+    continuation: bool,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(string: &'a str, config: &ParserConfig) -> Lines<'a> {
+        Lines { lines: string.split_inclusive('\n').enumerate(), offset: 0, continuation: config.line_continuation }
+    }
+
+    /// next physical line without line break
+    fn next_line(&mut self) -> Option<(usize, &'a str)> {
+        let (index, line) = self.lines.next()?;
+        self.offset += line.len();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some((index, line.strip_suffix('\r').unwrap_or(line)))
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, usize, Cow<'a, str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        let (index, line) = self.next_line()?;
+        if !self.continuation || !line.trim_end().ends_with('\\') {
+            return Some((index + 1, offset, Cow::Borrowed(line)));
+        }
+        let mut joined = String::new();
+        let mut current = line;
+        while let Some(stripped) = current.trim_end().strip_suffix('\\') {
+            joined.push_str(stripped);
+            match self.next_line() {
+                Some((_, next)) => current = next.trim_start(),
+                None => return Some((index + 1, offset, Cow::Owned(joined))),
+            }
+        }
+        joined.push_str(current);
+        Some((index + 1, offset, Cow::Owned(joined)))
+    }
+}
+
+/// split line into content and comment parts
+pub fn split_comment<'a>(line: &'a str, config: &ParserConfig) -> (&'a str, Option<&'a str>) {
+    let position = if config.inline_comments {
+        let start = if config.quoted_values { quoted_value_end(line, config) } else { 0 };
+        config
+            .comment_prefixes
+            .iter()
+            .filter_map(|p| line[start..].find(p.as_str()).map(|i| (start + i, p)))
+            .min_by_key(|&(i, _)| i)
+    } else {
+        let start = indentation(line);
+        config.comment_prefixes.iter().find(|p| line[start..].starts_with(p.as_str())).map(|p| (start, p))
+    };
+    match position {
+        Some((index, prefix)) => (&line[..index], Some(line[index..].trim_start_matches(prefix.as_str()))),
+        None => (line, None),
+    }
+}
+
+/// position after closing quote of quoted value in line or zero if value is not quoted
+fn quoted_value_end(line: &str, config: &ParserConfig) -> usize {
+    let value = match line.find(|c| config.delimiters.contains(&c)) {
+        Some(index) => &line[index + 1..],
+        None => return 0,
     };
+    let start = line.len() - value.trim_start().len();
+    closing_quote(&line[start..]).map_or(0, |end| start + end + 1)
+}
+
+/// split comment after closing bracket of section header
+fn split_header_comment<'a>(header: &'a str, config: &ParserConfig) -> (&'a str, Option<&'a str>) {
+    for (index, _) in header.match_indices(']') {
+        let rest = header[index + 1..].trim_start();
+        if let Some(prefix) = config.comment_prefixes.iter().find(|p| rest.starts_with(p.as_str())) {
+            return (&header[..=index], Some(rest[prefix.len()..].trim()).filter(|c| !c.is_empty()));
+        }
+    }
+    (header, None)
+}
+
+/// split git-style section name `type "subsection"` into type and unescaped subsection
+pub fn split_subsection(name: &str) -> Option<(&str, String)> {
+    let start = name.find('"')?;
+    let kind = name[..start].trim_end();
+    if kind.is_empty() {
+        return None;
+    }
+    unquote(&name[start..]).map(|subsection| (kind, subsection))
+}
+
+/// join section type and subsection into git-style section name
+pub fn join_subsection(kind: &str, subsection: &str) -> String {
+    format!("{} {}", kind, quote(subsection))
+}
+
+/// width of leading whitespace of line
+pub fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// get content of line without comment, if not empty
+pub fn line_content<'a>(line: &'a str, config: &ParserConfig) -> Option<&'a str> {
+    Some(split_comment(line, config).0.trim()).filter(|c| !c.is_empty())
+}
+
+/// parse single line of ini file
+pub fn parse_line(line: &str, index: usize, config: &ParserConfig) -> Result<Parsed, ParseError> {
+    let (raw_content, comment) = split_comment(line, config);
+    let start = indentation(raw_content);
+    let content = raw_content.trim();
+    let error = |kind, position| Err(ParseError::new(kind, index, line, position));
+    let decode = |s: &str| if config.unicode_escapes { unescape_unicode(s).into_owned() } else { s.to_owned() };
+    let comment = comment.map(|c| decode(c.trim())).filter(|c| !c.is_empty());
     if content.is_empty() {
         return Ok(Parsed::Empty);
     }
     // add checks for content
     if content.starts_with('[') {
+        // header comment is separated even if inline comments are disabled
+        let (content, comment) = match comment {
+            Some(comment) => (content, Some(comment)),
+            None => {
+                let (content, comment) = split_header_comment(content, config);
+                (content, comment.map(decode))
+            }
+        };
+        let inner = &content[1..];
+        let inner = inner.strip_suffix(']').unwrap_or(inner);
+        if config.strict && (inner.contains(&['[', ']'][..]) || inner.trim().is_empty()) {
+            let bracket = inner.find(&['[', ']'][..]).unwrap_or(0);
+            return error(ParseErrorKind::IncorrectSection, start + 1 + bracket);
+        }
         if content.ends_with(']') {
-            let section_name = content.trim_matches(|c| c == '[' || c == ']').to_owned();
-            return Ok(Parsed::Section(section_name));
+            let section_name = content.trim_matches(|c| c == '[' || c == ']');
+            let section_name = match split_subsection(section_name) {
+                Some((kind, subsection)) => join_subsection(&decode(kind), &subsection),
+                None => decode(section_name),
+            };
+            return Ok(Parsed::Section(section_name, comment));
         }
-        return Err(ParseError::IncorrectSection(index));
+        return error(ParseErrorKind::IncorrectSection, start + content.len());
     }
-    if content.contains('=') {
-        let mut pair = content.splitn(2, '=').map(|s| s.trim());
-        // if key is None => error
-        let key = match pair.next() {
-            Some(value) => value.to_owned(),
-            None => return Err(ParseError::EmptyKey(index)),
-        };
+    if let Some((delimiter, c)) = content.char_indices().find(|(_, c)| config.delimiters.contains(c)) {
+        let key = content[..delimiter].trim();
         if key.is_empty() {
-            return Err(ParseError::EmptyKey(index));
+            return error(ParseErrorKind::EmptyKey, start + delimiter);
         }
-        // if value is None => empty string
-        let value = match pair.next() {
-            Some(value) => value.to_owned(),
-            None => "".to_owned(),
+        let value_start = start + delimiter + c.len_utf8();
+        // untrimmed value lasts up to comment or end of line
+        let raw_value = &raw_content[value_start..];
+        let value = raw_value.trim();
+        let value = match unquote(value).filter(|_| config.quoted_values) {
+            Some(unquoted) => unquoted,
+            None if config.quoted_values && config.strict && value.starts_with(&['"', '\''][..]) => {
+                return error(ParseErrorKind::IncorrectSyntax, value_start + indentation(raw_value));
+            }
+            None if config.trim_values => decode(value),
+            None => decode(raw_value),
         };
-        return Ok(Parsed::Value(key, value));
+        return Ok(Parsed::Value(decode(key), value, comment));
+    }
+    if config.allow_bare_keys {
+        return Ok(Parsed::Value(decode(content), String::new(), comment));
     }
-    Err(ParseError::IncorrectSyntax(index))
+    error(ParseErrorKind::IncorrectSyntax, start)
 }
 
 #[cfg(test)]
@@ -59,123 +200,256 @@ mod test {
 
     #[test]
     fn comment() -> Result<(), Error> {
-        match parse_line(";------", 0)? {
-            Parsed::Empty => assert!(true),
-            _ => assert!(false),
+        match parse_line(";------", 0, &ParserConfig::default())? {
+            Parsed::Empty => (),
+            _ => unreachable!(),
         }
         Ok(())
     }
 
     #[test]
     fn entry() -> Result<(), Error> {
-        match parse_line("name1 = 100 ; comment", 0)? {
-            Parsed::Value(name, text) => {
+        match parse_line("name1 = 100 ; comment", 0, &ParserConfig::default())? {
+            Parsed::Value(name, text, comment) => {
                 assert_eq!(name, String::from("name1"));
                 assert_eq!(text, String::from("100"));
+                assert_eq!(comment, Some(String::from("comment")));
             }
-            _ => assert!(false),
+            _ => unreachable!(),
         }
         Ok(())
     }
 
     #[test]
     fn section() -> Result<(), Error> {
-        match parse_line("[section]", 0)? {
-            Parsed::Section(name) => assert_eq!(name, String::from("section")),
-            _ => assert!(false),
+        match parse_line("[section]", 0, &ParserConfig::default())? {
+            Parsed::Section(name, comment) => {
+                assert_eq!(name, String::from("section"));
+                assert_eq!(comment, None);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn section_comment() -> Result<(), Error> {
+        let configs = [ParserConfig::default(), ParserConfig::default().inline_comments(false)];
+        for (line, config) in
+            [("[network]   ; adapter settings", &configs[0]), ("[network]\t# adapter settings", &configs[1])]
+        {
+            match parse_line(line, 0, config)? {
+                Parsed::Section(name, comment) => {
+                    assert_eq!(name, "network");
+                    assert_eq!(comment.as_deref(), Some("adapter settings"));
+                }
+                _ => unreachable!(),
+            }
         }
+        assert!(parse_line("[network] adapter", 0, &configs[1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn subsection() -> Result<(), Error> {
+        match parse_line("[remote   \"my \\\"origin\\\"\"] ; comment", 0, &ParserConfig::default())? {
+            Parsed::Section(name, _) => {
+                assert_eq!(name, "remote \"my \\\"origin\\\"\"");
+                assert_eq!(split_subsection(&name), Some(("remote", String::from("my \"origin\""))));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(split_subsection("\"origin\""), None);
+        assert_eq!(split_subsection("remote.origin"), None);
         Ok(())
     }
 
     #[test]
     fn weird_name() -> Result<(), Error> {
-        match parse_line("_.,:(){}-@&*| = 100 ; so weird", 0)? {
-            Parsed::Value(name, text) => {
+        match parse_line("_.,:(){}-@&*| = 100 ; so weird", 0, &ParserConfig::default())? {
+            Parsed::Value(name, text, _) => {
                 assert_eq!(name, String::from("_.,:(){}-@&*|"));
                 assert_eq!(text, String::from("100"));
             }
-            _ => assert!(false),
+            _ => unreachable!(),
         }
         Ok(())
     }
 
     #[test]
     fn weird_section() -> Result<(), Error> {
-        match parse_line("[[abc]] ; omg", 0)? {
-            Parsed::Section(name) => assert_eq!(name, String::from("abc")),
-            _ => assert!(false),
+        match parse_line("[[abc]] ; omg", 0, &ParserConfig::default())? {
+            Parsed::Section(name, _) => assert_eq!(name, String::from("abc")),
+            _ => unreachable!(),
         }
         Ok(())
     }
 
     #[test]
     fn text_entry() -> Result<(), Error> {
-        match parse_line("text_name = hello world!", 0)? {
-            Parsed::Value(name, text) => {
+        match parse_line("text_name = hello world!", 0, &ParserConfig::default())? {
+            Parsed::Value(name, text, _) => {
                 assert_eq!(name, String::from("text_name"));
                 assert_eq!(text, String::from("hello world!"));
             }
-            _ => assert!(false),
+            _ => unreachable!(),
         }
         Ok(())
     }
 
     #[test]
     fn incorrect_token() {
-        match parse_line("[section = 1, 2 = value", 0) {
-            Err(_) => assert!(true),
-            _ => assert!(false),
+        match parse_line("[section = 1, 2 = value", 0, &ParserConfig::default()) {
+            Err(_) => (),
+            _ => unreachable!(),
         }
     }
 
     #[test]
     fn empty_key() {
-        match parse_line("= 3", 0) {
-            Err(_) => assert!(true),
-            _ => assert!(false),
+        match parse_line("= 3", 0, &ParserConfig::default()) {
+            Err(_) => (),
+            _ => unreachable!(),
         }
     }
 
     #[test]
     fn empty_kv() {
-        match parse_line("=", 0) {
-            Err(_) => assert!(true),
-            _ => assert!(false),
+        match parse_line("=", 0, &ParserConfig::default()) {
+            Err(_) => (),
+            _ => unreachable!(),
         }
     }
 
     #[test]
     fn empty_value() -> Result<(), Error> {
-        match parse_line("a =", 0)? {
-            Parsed::Value(key, value) => {
+        match parse_line("a =", 0, &ParserConfig::default())? {
+            Parsed::Value(key, value, _) => {
                 assert_eq!(key, String::from("a"));
                 assert_eq!(value.len(), 0);
             }
-            _ => assert!(false),
+            _ => unreachable!(),
         }
         Ok(())
     }
 
     #[test]
     fn empty_value_with_comment() -> Result<(), Error> {
-        match parse_line("a = ; comment line", 0)? {
-            Parsed::Value(key, value) => {
+        match parse_line("a = ; comment line", 0, &ParserConfig::default())? {
+            Parsed::Value(key, value, _) => {
                 assert_eq!(key, String::from("a"));
                 assert_eq!(value.len(), 0);
             }
-            _ => assert!(false),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn custom_comment_prefix() -> Result<(), Error> {
+        let config = ParserConfig::default().comment_prefixes(["//"]);
+        match parse_line("url = http://a.b // link", 0, &config)? {
+            Parsed::Value(key, value, comment) => {
+                assert_eq!(key, "url");
+                assert_eq!(value, "http:");
+                assert_eq!(comment.as_deref(), Some("a.b // link"));
+            }
+            _ => unreachable!(),
+        }
+        match parse_line("  // a = 1", 0, &config.inline_comments(false))? {
+            Parsed::Empty => (),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn continuation() {
+        let config = ParserConfig::default().line_continuation(true);
+        let lines: Vec<_> = Lines::new("a = 1 \\\n  2 \\\n\t3\nb = 4\\", &config).collect();
+        assert_eq!(lines, [(1, 0, Cow::Borrowed("a = 1 2 3")), (4, 17, Cow::Borrowed("b = 4"))]);
+    }
+
+    #[test]
+    fn quoted_value() -> Result<(), Error> {
+        let config = ParserConfig::default().quoted_values(true);
+        match parse_line(r#"a = "x = \"1\" ; # 2" # comment"#, 0, &config)? {
+            Parsed::Value(key, value, comment) => {
+                assert_eq!(key, "a");
+                assert_eq!(value, r#"x = "1" ; # 2"#);
+                assert_eq!(comment.as_deref(), Some("comment"));
+            }
+            _ => unreachable!(),
+        }
+        match parse_line("a = it's ; comment", 0, &config)? {
+            Parsed::Value(_, value, _) => assert_eq!(value, "it's"),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn untrimmed_value() -> Result<(), Error> {
+        let config = ParserConfig::default().quoted_values(true).trim_values(false);
+        for (line, expected) in [("a = \" x \" ; c", " x "), ("a =  x  ; c", "  x  "), ("a=", "")] {
+            match parse_line(line, 0, &config)? {
+                Parsed::Value(_, value, _) => assert_eq!(value, expected),
+                _ => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn colon_delimiter() -> Result<(), Error> {
+        match parse_line("url: http://a.b = c", 0, &ParserConfig::default().colon_delimiter(true))? {
+            Parsed::Value(key, value, _) => {
+                assert_eq!(key, "url");
+                assert_eq!(value, "http://a.b = c");
+            }
+            _ => unreachable!(),
+        }
+        match parse_line("a:b = c", 0, &ParserConfig::default())? {
+            Parsed::Value(key, value, _) => {
+                assert_eq!(key, "a:b");
+                assert_eq!(value, "c");
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn strict() {
+        let config = ParserConfig::default().strict(true).quoted_values(true);
+        for line in &["[[a]]", "[]", "[a]]", "a = \"b", "a = 'b\\'"] {
+            assert!(parse_line(line, 0, &config).is_err(), "{}", line);
+        }
+        assert!(parse_line("[remote \"origin\"]", 0, &config).is_ok());
+    }
+
+    #[test]
+    fn bare_key() -> Result<(), Error> {
+        match parse_line("enable-foo ; flag", 0, &ParserConfig::default().allow_bare_keys(true))? {
+            Parsed::Value(key, value, comment) => {
+                assert_eq!(key, "enable-foo");
+                assert_eq!(value, "");
+                assert_eq!(comment.as_deref(), Some("flag"));
+            }
+            _ => unreachable!(),
         }
+        assert!(parse_line("enable-foo", 0, &ParserConfig::default()).is_err());
         Ok(())
     }
 
     #[test]
     fn unix_comment() -> Result<(), Error> {
-        match parse_line("a = 3 # 42", 0)? {
-            Parsed::Value(key, value) => {
+        match parse_line("a = 3 # 42", 0, &ParserConfig::default())? {
+            Parsed::Value(key, value, _) => {
                 assert_eq!(key, String::from("a"));
                 assert_eq!(value, "3");
             }
-            _ => assert!(false),
+            _ => unreachable!(),
         }
         Ok(())
     }
@@ -0,0 +1,503 @@
+//! Section module
+//!
+//! Contains [Section] which holds keys and values of a single section and entry types to edit it in place
+use crate::ordered_hashmap::OrderedHashMap;
+use crate::{value, FromIniValue, GetError, Ini, SectionIter, SectionIterMut, ToIniValue};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::ops::Index;
+use std::time::Duration;
+
+/// Keys and values of a single section in order of appearance
+///
+/// Section can be borrowed from document with [`Ini::get_section()`](crate::Ini::get_section) or taken out of
+/// it with [`Ini::remove_section()`](crate::Ini::remove_section), so code can work with a single section.
+/// Getters have the same meaning as getters of [Ini] with the same name.
+/// Keys of case-insensitive document are stored in lower case and looked up in any case.
+///
+/// Sections are equal if they have the same keys and values regardless of order,
+/// use [`eq_ordered()`](Section::eq_ordered) to compare order too.
+///
+/// # Example
+/// ```
+/// # use tini::{Ini, Section};
+/// let mut conf = Ini::from_string("[legacy]\nport = 8080\nhost = localhost").unwrap();
+/// let section = conf.remove_section("legacy").unwrap();
+///
+/// assert_eq!(section.len(), 2);
+/// assert_eq!(section.get::<u16>("port"), Some(8080));
+/// assert_eq!(section.get_raw("host"), Some("localhost"));
+///
+/// fn port(section: &Section) -> u16 {
+///     section.get_int("port").unwrap_or(80)
+/// }
+/// assert_eq!(port(&section), 8080);
+/// assert_eq!(port(&Section::new().set("port", "none")), 80);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Section {
+    pub(crate) values: OrderedHashMap<String, String>,
+    case_insensitive: bool,
+}
+
+impl Section {
+    /// Create an empty section (similar to [Section::default])
+    pub fn new() -> Section {
+        Section { values: OrderedHashMap::new(), case_insensitive: false }
+    }
+
+    /// Create an empty section of document with given case sensitivity
+    pub(crate) fn with_case(case_insensitive: bool) -> Section {
+        Section { values: OrderedHashMap::new(), case_insensitive }
+    }
+
+    /// Private method which converts key to lower case for case-insensitive document
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if self.case_insensitive {
+            Cow::Owned(key.to_lowercase())
+        } else {
+            Cow::Borrowed(key)
+        }
+    }
+
+    /// Get scalar value of `key`
+    ///
+    /// - output type `T` must implement [FromIniValue] trait for auto conversion
+    pub fn get<T>(&self, key: &str) -> Option<T>
+    where
+        T: FromIniValue,
+    {
+        self.get_raw(key).and_then(|x| T::parse_value(x))
+    }
+
+    /// Get value of `key` converted by `parse`, see [`Ini::get_with()`](crate::Ini::get_with)
+    pub fn get_with<T, E, F>(&self, key: &str, parse: F) -> Option<T>
+    where
+        F: FnOnce(&str) -> Result<T, E>,
+    {
+        self.get_raw(key).and_then(|x| parse(x).ok())
+    }
+
+    /// Get value of `key` as is
+    pub fn get_raw(&self, key: &str) -> Option<&str> {
+        self.values.get(&*self.normalize(key)).map(String::as_str)
+    }
+
+    /// Get scalar value of `key`, telling apart absent key, empty value and invalid value,
+    /// see [`Ini::try_get()`](crate::Ini::try_get)
+    ///
+    /// # Errors
+    /// [GetError::MissingKey], [GetError::Empty] or [GetError::ParseFailed]
+    pub fn try_get<T>(&self, key: &str) -> Result<T, GetError>
+    where
+        T: FromIniValue,
+    {
+        value::try_parse(self.get_raw(key))
+    }
+
+    /// Get vector value of `key` separated by `,`, see [`Ini::get_vec()`](crate::Ini::get_vec)
+    pub fn get_vec<T>(&self, key: &str) -> Option<Vec<T>>
+    where
+        T: FromIniValue,
+    {
+        self.get_vec_with_sep(key, ",")
+    }
+
+    /// Get vector value of `key` separated by `sep`, see [`Ini::get_vec_with_sep()`](crate::Ini::get_vec_with_sep)
+    pub fn get_vec_with_sep<T>(&self, key: &str, sep: &str) -> Option<Vec<T>>
+    where
+        T: FromIniValue,
+    {
+        self.get_raw(key).and_then(|x| x.split(sep).map(|s| T::parse_value(s.trim())).collect::<Option<Vec<T>>>())
+    }
+
+    /// Get vector value of `key` separated by `,`, telling which element is invalid,
+    /// see [`Ini::try_get_vec()`](crate::Ini::try_get_vec)
+    ///
+    /// # Errors
+    /// [GetError::MissingKey], [GetError::Empty] or [GetError::InvalidElement]
+    pub fn try_get_vec<T>(&self, key: &str) -> Result<Vec<T>, GetError>
+    where
+        T: FromIniValue,
+    {
+        self.try_get_vec_with_sep(key, ",")
+    }
+
+    /// Get vector value of `key` separated by `sep`, telling which element is invalid,
+    /// see [`Ini::try_get_vec_with_sep()`](crate::Ini::try_get_vec_with_sep)
+    ///
+    /// # Errors
+    /// [GetError::MissingKey], [GetError::Empty] or [GetError::InvalidElement]
+    pub fn try_get_vec_with_sep<T>(&self, key: &str, sep: &str) -> Result<Vec<T>, GetError>
+    where
+        T: FromIniValue,
+    {
+        value::try_parse_vec(self.get_raw(key), sep)
+    }
+
+    /// Get boolean value of `key`, see [`Ini::get_bool()`](crate::Ini::get_bool)
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get_raw(key).and_then(value::parse_bool)
+    }
+
+    /// Get integer value of `key`, see [`Ini::get_int()`](crate::Ini::get_int)
+    pub fn get_int<T>(&self, key: &str) -> Option<T>
+    where
+        T: TryFrom<i128>,
+    {
+        self.get_raw(key).and_then(value::parse_int).and_then(|x| T::try_from(x).ok())
+    }
+
+    /// Get duration value of `key`, see [`Ini::get_duration()`](crate::Ini::get_duration)
+    pub fn get_duration(&self, key: &str) -> Option<Duration> {
+        self.get_raw(key).and_then(value::parse_duration)
+    }
+
+    /// Get size in bytes of `key`, see [`Ini::get_size()`](crate::Ini::get_size)
+    pub fn get_size(&self, key: &str) -> Option<u64> {
+        self.get_raw(key).and_then(value::parse_size)
+    }
+
+    /// Set `value` of `key`, key is added to the end of section if it is absent
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Section;
+    /// let section = Section::new().set("a", 1).set("b", true).set("a", 2);
+    ///
+    /// assert_eq!(section.iter().collect::<Vec<_>>(), [(&"a".to_owned(), &"2".to_owned()), (&"b".to_owned(), &"true".to_owned())]);
+    /// ```
+    pub fn set<V>(mut self, key: &str, value: V) -> Self
+    where
+        V: ToIniValue,
+    {
+        self.insert(key, value);
+        self
+    }
+
+    /// Like [`set()`](Section::set), but doesn't consume section, returns the previous value of `key`
+    pub fn insert<V>(&mut self, key: &str, value: V) -> Option<String>
+    where
+        V: ToIniValue,
+    {
+        let key = self.normalize(key).into_owned();
+        self.values.insert(key, value.format_value())
+    }
+
+    /// Like [`insert()`](Section::insert), but puts `key` at position `index`, or at the end if `index` is out of bounds.
+    /// Present key is moved to `index`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Section;
+    /// let mut section = Section::new().set("host", "localhost").set("user", "admin");
+    /// section.insert_at(1, "port", 8080);
+    ///
+    /// assert_eq!(section.keys().collect::<Vec<_>>(), ["host", "port", "user"]);
+    /// ```
+    pub fn insert_at<V>(&mut self, index: usize, key: &str, value: V) -> Option<String>
+    where
+        V: ToIniValue,
+    {
+        let key = self.normalize(key).into_owned();
+        self.values.insert_at(index, key, value.format_value())
+    }
+
+    /// Move `key` to position `index`, or to the end if `index` is out of bounds.
+    /// Returns `false` if key is absent.
+    pub fn move_key(&mut self, key: &str, index: usize) -> bool {
+        let key = self.normalize(key).into_owned();
+        self.values.move_key(&key, index)
+    }
+
+    /// Remove `key` and return its value, if it was present
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let key = self.normalize(key).into_owned();
+        self.values.remove(&key)
+    }
+
+    /// Reserve space for at least `additional` more keys
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+    }
+
+    /// Keep only keys for which `f` returns `true`
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[section]\na = 1\nb =\nc = 3").unwrap();
+    ///
+    /// if let Some(section) = conf.get_section_mut("section") {
+    ///     section.retain(|_, value| !value.is_empty());
+    /// }
+    /// assert_eq!(conf.to_string(), "[section]\na = 1\nc = 3\n");
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, &str) -> bool,
+    {
+        let removed: Vec<String> =
+            self.values.iter().filter(|(key, value)| !f(key, value)).map(|(key, _)| key.clone()).collect();
+        for key in removed {
+            self.values.remove(&key);
+        }
+    }
+
+    /// Iterate over keys in order of appearance
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Iterate over values in order of appearance
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[section]\na = 1\nb = 2").unwrap();
+    ///
+    /// assert_eq!(conf["section"].keys().collect::<Vec<_>>(), ["a", "b"]);
+    /// assert_eq!(conf["section"].values().collect::<Vec<_>>(), ["1", "2"]);
+    /// ```
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.values.iter().map(|(_, value)| value.as_str())
+    }
+
+    /// Iterate over mutable values in order of appearance
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let mut conf = Ini::from_string("[section]\na = 1\nb = 2").unwrap();
+    ///
+    /// for value in conf["section"].values_mut() {
+    ///     value.push('0');
+    /// }
+    /// assert_eq!(conf.to_string(), "[section]\na = 10\nb = 20\n");
+    /// ```
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        self.values.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Check if `key` is present, regardless of its value
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(&*self.normalize(key))
+    }
+
+    /// Number of keys in section
+    pub fn len(&self) -> usize {
+        self.values.keys().len()
+    }
+
+    /// Check if section has no keys
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over keys and values in order of appearance
+    pub fn iter(&self) -> SectionIter<'_> {
+        SectionIter { document: self, iter: self.values.iter() }
+    }
+
+    /// Iterate over keys starting with `prefix` and their values in order of appearance,
+    /// removing `prefix` from yielded keys if `strip` is `true`
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let conf = Ini::from_string("[net]\ndns_1 = 1.1.1.1\ngateway = 10.0.0.1\ndns_2 = 8.8.8.8").unwrap();
+    /// let net = conf.get_section("net").unwrap();
+    ///
+    /// let servers: Vec<(&str, &str)> = net.with_prefix("dns_", true).collect();
+    /// assert_eq!(servers, [("1", "1.1.1.1"), ("2", "8.8.8.8")]);
+    /// assert_eq!(net.with_prefix("dns_", false).next(), Some(("dns_1", "1.1.1.1")));
+    /// ```
+    pub fn with_prefix<'a>(&'a self, prefix: &str, strip: bool) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        let prefix = self.normalize(prefix).into_owned();
+        self.values.iter().filter_map(move |(key, value)| {
+            let stripped = key.strip_prefix(prefix.as_str())?;
+            Some((if strip { stripped } else { key.as_str() }, value.as_str()))
+        })
+    }
+
+    /// Iterate over keys and mutable values in order of appearance
+    pub fn iter_mut(&mut self) -> SectionIterMut<'_> {
+        SectionIterMut { iter: self.values.iter_mut() }
+    }
+
+    /// Check if sections have the same keys and values in the same order
+    ///
+    /// # Example
+    /// ```
+    /// # use tini::Ini;
+    /// let a = Ini::from_string("[s]\nx = 1\ny = 2").unwrap().remove_section("s").unwrap();
+    /// let b = Ini::from_string("[s]\ny = 2\nx = 1").unwrap().remove_section("s").unwrap();
+    ///
+    /// assert_eq!(a, b);
+    /// assert!(!a.eq_ordered(&b));
+    /// ```
+    pub fn eq_ordered(&self, other: &Section) -> bool {
+        self.values.iter().eq(other.values.iter())
+    }
+
+    /// Get entry of `key` for in-place manipulation, see [`Ini::entry()`](crate::Ini::entry)
+    pub fn entry(&mut self, key: &str) -> KeyEntry<'_> {
+        let key = self.normalize(key).into_owned();
+        KeyEntry { section: self, key }
+    }
+}
+
+impl<'a> IntoIterator for &'a Section {
+    type Item = (&'a String, &'a String);
+    type IntoIter = SectionIter<'a>;
+
+    fn into_iter(self) -> SectionIter<'a> {
+        self.iter()
+    }
+}
+
+/// Collect section from key-value pairs
+///
+/// # Example
+/// ```
+/// # use tini::Section;
+/// let section: Section = vec![("a", 1), ("b", 2)].into_iter().collect();
+///
+/// assert_eq!(section.get::<u8>("b"), Some(2));
+/// ```
+impl<K, V> FromIterator<(K, V)> for Section
+where
+    K: Into<String>,
+    V: ToIniValue,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut section = Section::new();
+        section.extend(iter);
+        section
+    }
+}
+
+/// Add key-value pairs like [`insert()`](Section::insert)
+impl<K, V> Extend<(K, V)> for Section
+where
+    K: Into<String>,
+    V: ToIniValue,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(&key.into(), value);
+        }
+    }
+}
+
+impl PartialEq for Section {
+    fn eq(&self, other: &Section) -> bool {
+        self.len() == other.len() && self.values.iter().all(|(key, value)| other.values.get(key) == Some(value))
+    }
+}
+
+impl Eq for Section {}
+
+/// Get value of key as is
+///
+/// # Panics
+/// Panics if key is absent, use [`get_raw()`](Section::get_raw) to handle absent keys
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let conf = Ini::from_string("[server]\nhost = localhost").unwrap();
+///
+/// assert_eq!(&conf["server"]["host"], "localhost");
+/// ```
+impl Index<&str> for Section {
+    type Output = str;
+
+    fn index(&self, key: &str) -> &str {
+        match self.get_raw(key) {
+            Some(value) => value,
+            None => panic!("key `{}` is not found in section", key),
+        }
+    }
+}
+
+/// Entry of a section in [Ini], created by [`Ini::entry()`](crate::Ini::entry)
+pub struct SectionEntry<'a> {
+    pub(crate) ini: &'a mut Ini,
+    pub(crate) name: String,
+}
+
+impl<'a> SectionEntry<'a> {
+    /// Name of the section
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the section, adding an empty one to the end of document if it is absent
+    pub fn or_default(self) -> &'a mut Section {
+        self.ini.section_mut(self.name)
+    }
+}
+
+/// Entry of a key in [Section], created by [`Section::entry()`]
+pub struct KeyEntry<'a> {
+    section: &'a mut Section,
+    key: String,
+}
+
+impl<'a> KeyEntry<'a> {
+    /// Name of the key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Get value of the key, adding it with `default` value to the end of section if it is absent
+    pub fn or_insert<V>(self, default: V) -> &'a mut String
+    where
+        V: ToIniValue,
+    {
+        self.or_insert_with(|| default.format_value())
+    }
+
+    /// Get value of the key, adding it with result of `default` to the end of section if it is absent
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut String
+    where
+        F: FnOnce() -> String,
+    {
+        self.section.values.entry(self.key).or_insert_with(default)
+    }
+
+    /// Modify value of the key with `f` if it is present
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut String),
+    {
+        if let Some(value) = self.section.values.get_mut(&self.key) {
+            f(value);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn getters() {
+        let mut section = Section::with_case(true).set("Timeout", "5s").set("list", "1, 2").set("empty", "");
+        assert_eq!(section.get_duration("TIMEOUT"), Some(Duration::from_secs(5)));
+        assert_eq!(section.get_vec::<u8>("list"), Some(vec![1, 2]));
+        assert_eq!(section.try_get::<u8>("empty"), Err(GetError::Empty));
+        assert_eq!(section.try_get::<u8>("other"), Err(GetError::MissingKey));
+        assert_eq!(section.try_get_vec::<u8>("list"), Ok(vec![1, 2]));
+        assert_eq!(
+            section.try_get_vec_with_sep::<u8>("list", " "),
+            Err(GetError::InvalidElement { index: 0, value: "1,".to_owned(), type_name: "u8" })
+        );
+        assert_eq!(section.remove("LIST"), Some("1, 2".to_owned()));
+        assert_eq!(section.keys().collect::<Vec<_>>(), ["timeout", "empty"]);
+        assert_eq!(section.with_prefix("TIME", true).collect::<Vec<_>>(), [("out", "5s")]);
+    }
+}
@@ -0,0 +1,836 @@
+//! Serde module
+//!
+//! Contains [Serialize] and [Deserialize] implementations for [Ini] and [Section] and bridging of
+//! serde structures through documents with [from_ini] and [to_ini], enabled with `serde` feature
+use crate::{Ini, Section};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Impossible, SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize, Serializer};
+use std::error;
+use std::fmt;
+
+/// Error of converting structure with [from_ini] or [to_ini]
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// let ini = Ini::from_string("[server]\nport = many").unwrap();
+/// let error = tini::from_ini::<std::collections::HashMap<String, std::collections::HashMap<String, u16>>>(&ini);
+/// assert_eq!(error.unwrap_err().to_string(), "invalid value `many` of key `port`");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError(String);
+
+impl error::Error for SerdeError {}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError(msg.to_string())
+    }
+}
+
+/// Build structure from document, which is seen as map of section names to maps of key names to values
+///
+/// Values are parsed to the type of field on demand, lists are split by `,` like in
+/// [`get_vec()`](Ini::get_vec), empty values become [None] for [Option] fields.
+/// Keys before the first section header are in section with empty name, e.g. `#[serde(rename = "")]` field.
+///
+/// # Errors
+/// [SerdeError] with description of absent or invalid section or key
+///
+/// # Example
+/// ```
+/// # use tini::Ini;
+/// # use std::collections::HashMap;
+/// let ini = Ini::from_string("top = 1\n[server]\nports = 80, 443").unwrap();
+/// let ports: HashMap<String, HashMap<String, Vec<u16>>> = tini::from_ini(&ini).unwrap();
+/// assert_eq!(ports["server"]["ports"], [80, 443]);
+/// assert_eq!(ports[""]["top"], [1]);
+///
+/// let ini = Ini::from_string("[server]\nread = 30\nwrite =").unwrap();
+/// let timeouts: HashMap<String, HashMap<String, Option<u32>>> = tini::from_ini(&ini).unwrap();
+/// assert_eq!((timeouts["server"]["read"], timeouts["server"]["write"]), (Some(30), None));
+/// ```
+pub fn from_ini<T>(ini: &Ini) -> Result<T, SerdeError>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(IniDeserializer(ini))
+}
+
+/// Write structure to new document, fields of which must be maps or structures of values
+///
+/// Values are formatted as with [`set()`](Ini::set), sequences are joined with `, ` like in
+/// [`set_vec()`](Ini::set_vec), [None] values are skipped.
+///
+/// # Errors
+/// [SerdeError] if structure is not a map of maps or contains values nested deeper than that
+///
+/// # Example
+/// ```
+/// # use std::collections::BTreeMap;
+/// let mut server = BTreeMap::new();
+/// server.insert("ports", vec![80, 443]);
+/// let mut config = BTreeMap::new();
+/// config.insert("server", server);
+/// let ini = tini::to_ini(&config).unwrap();
+///
+/// assert_eq!(ini.to_string(), "[server]\nports = 80, 443\n");
+/// assert!(tini::to_ini(&42).is_err());
+/// ```
+pub fn to_ini<T>(value: &T) -> Result<Ini, SerdeError>
+where
+    T: Serialize + ?Sized,
+{
+    let sections = match value.serialize(NodeSerializer)? {
+        Node::Map(sections) => sections,
+        _ => return Err(SerdeError("expected map of sections".to_owned())),
+    };
+    let mut result = Ini::new();
+    for (name, section) in sections {
+        let entries = match section {
+            Node::Map(entries) => entries,
+            _ => return Err(SerdeError(format!("expected map of keys in section `{}`", name))),
+        };
+        result.section_mut(name.clone());
+        for (key, value) in entries {
+            match value {
+                Node::Value(Some(value)) => result.set(&name, &key, value),
+                Node::Value(None) => {}
+                _ => return Err(SerdeError(format!("nested value of key `{}` in section `{}`", key, name))),
+            }
+        }
+    }
+    result.modified = false;
+    Ok(result)
+}
+
+/// Document is serialized as map of section names to maps of string values, keys before the first section header
+/// are placed into section with empty name if there are any
+impl Serialize for Ini {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let sections: Vec<_> =
+            self.document.iter().filter(|(name, section)| !name.is_empty() || !section.is_empty()).collect();
+        let mut map = serializer.serialize_map(Some(sections.len()))?;
+        for (name, section) in sections {
+            map.serialize_entry(name, section)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Section {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.values.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Values may be strings, numbers, booleans, nulls or lists of them, lists are joined with `, `
+impl<'de> Deserialize<'de> for Ini {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IniVisitor;
+
+        impl<'de> Visitor<'de> for IniVisitor {
+            type Value = Ini;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("map of sections")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Ini, A::Error> {
+                let mut result = Ini::new();
+                while let Some(name) = map.next_key::<String>()? {
+                    let section = map.next_value::<Section>()?;
+                    result.section_mut(name.clone());
+                    for (key, value) in section.values.iter() {
+                        result.set(&name, key, value);
+                    }
+                }
+                result.modified = false;
+                Ok(result)
+            }
+        }
+
+        deserializer.deserialize_map(IniVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Section {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SectionVisitor;
+
+        impl<'de> Visitor<'de> for SectionVisitor {
+            type Value = Section;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("map of keys")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Section, A::Error> {
+                let mut result = Section::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    let value = map.next_value::<RawValue>()?;
+                    result.values.insert(key, value.0);
+                }
+                Ok(result)
+            }
+        }
+
+        deserializer.deserialize_map(SectionVisitor)
+    }
+}
+
+/// Private value of key: string, number, boolean, unit or list of them
+struct RawValue(String);
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = RawValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("string, number, boolean, null or list of them")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<RawValue, E> {
+                Ok(RawValue(v.to_owned()))
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<RawValue, E> {
+                Ok(RawValue(v.to_string()))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<RawValue, E> {
+                Ok(RawValue(v.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<RawValue, E> {
+                Ok(RawValue(v.to_string()))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<RawValue, E> {
+                Ok(RawValue(v.to_string()))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<RawValue, E> {
+                Ok(RawValue(String::new()))
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<RawValue, E> {
+                Ok(RawValue(String::new()))
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<RawValue, D::Error> {
+                RawValue::deserialize(deserializer)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RawValue, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element::<RawValue>()? {
+                    items.push(item.0);
+                }
+                Ok(RawValue(items.join(", ")))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Private deserializer of the whole document as map of sections
+struct IniDeserializer<'a>(&'a Ini);
+
+impl<'de, 'a> Deserializer<'de> for IniDeserializer<'a> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let sections = self.0.document.iter().filter(|(name, section)| !name.is_empty() || !section.is_empty());
+        let entries = sections.map(|(name, section)| (name.as_str(), SectionDeserializer(section)));
+        visitor.visit_map(EntriesAccess { entries, next: None })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Private deserializer of section as map of keys
+struct SectionDeserializer<'a>(&'a Section);
+
+impl<'de, 'a> Deserializer<'de> for SectionDeserializer<'a> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let entries = self.0.values.iter().map(|(key, value)| (key.as_str(), ValueDeserializer { key, value }));
+        visitor.visit_map(EntriesAccess { entries, next: None })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Private map access over names and deserializers of their contents
+struct EntriesAccess<I, D> {
+    entries: I,
+    next: Option<D>,
+}
+
+impl<'de, 'a, I, D> MapAccess<'de> for EntriesAccess<I, D>
+where
+    I: Iterator<Item = (&'a str, D)>,
+    D: Deserializer<'de, Error = SerdeError>,
+{
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SerdeError> {
+        match self.entries.next() {
+            Some((name, next)) => {
+                self.next = Some(next);
+                seed.deserialize(IntoDeserializer::<SerdeError>::into_deserializer(name)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerdeError> {
+        match self.next.take() {
+            Some(next) => seed.deserialize(next),
+            None => Err(SerdeError("value is requested before key".to_owned())),
+        }
+    }
+}
+
+/// Private deserializer of value which is parsed to requested type
+#[derive(Clone, Copy)]
+struct ValueDeserializer<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn parse<T: std::str::FromStr>(self) -> Result<T, SerdeError> {
+        self.value
+            .trim()
+            .parse()
+            .map_err(|_| SerdeError(format!("invalid value `{}` of key `{}`", self.value, self.key)))
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, SerdeError> for ValueDeserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Private macro which implements deserialization of scalar types parsed with [FromStr](std::str::FromStr)
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_str(self.value)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let key = self.key;
+        let items = self.value.split(',').filter(|_| !self.value.trim().is_empty());
+        let items = items.map(|value| ValueDeserializer { key, value: value.trim() });
+        visitor.visit_seq(de::value::SeqDeserializer::new(items))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self, name: &'static str, variants: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        IntoDeserializer::<SerdeError>::into_deserializer(self.value).deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Private tree of serialized structure: value of key, absent for [None], or map of names to nodes
+enum Node {
+    Value(Option<String>),
+    Map(Vec<(String, Node)>),
+}
+
+/// Private serializer of structure to tree of maps and values
+struct NodeSerializer;
+
+impl NodeSerializer {
+    fn value<T: fmt::Display>(value: T) -> Result<Node, SerdeError> {
+        Ok(Node::Value(Some(value.to_string())))
+    }
+
+    fn unsupported(kind: &str) -> SerdeError {
+        SerdeError(format!("{} can not be written to ini", kind))
+    }
+}
+
+impl Serializer for NodeSerializer {
+    type Ok = Node;
+    type Error = SerdeError;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = Impossible<Node, SerdeError>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = Impossible<Node, SerdeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Node, SerdeError> {
+        Self::value(v)
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Node, SerdeError> {
+        Err(Self::unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Node, SerdeError> {
+        Ok(Node::Value(None))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Node, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Node, SerdeError> {
+        Self::value("")
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Node, SerdeError> {
+        Self::value("")
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<Node, SerdeError> {
+        Self::value(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<Node, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self, _: &'static str, _: u32, _: &'static str, _: &T,
+    ) -> Result<Node, SerdeError> {
+        Err(Self::unsupported("enum variant with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ListSerializer, SerdeError> {
+        Ok(ListSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, len: usize) -> Result<ListSerializer, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self, _: &'static str, _: u32, _: &'static str, _: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(Self::unsupported("enum variant with data"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, SerdeError> {
+        Ok(MapSerializer { entries: Vec::with_capacity(len.unwrap_or(0)), key: None })
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<MapSerializer, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self, _: &'static str, _: u32, _: &'static str, _: usize,
+    ) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(Self::unsupported("enum variant with data"))
+    }
+}
+
+/// Private serializer of sequence to list of values joined with `, `
+struct ListSerializer(Vec<String>);
+
+impl SerializeSeq for ListSerializer {
+    type Ok = Node;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        match value.serialize(NodeSerializer)? {
+            Node::Value(Some(value)) => {
+                self.0.push(value);
+                Ok(())
+            }
+            _ => Err(NodeSerializer::unsupported("list of absent or nested values")),
+        }
+    }
+
+    fn end(self) -> Result<Node, SerdeError> {
+        Ok(Node::Value(Some(self.0.join(", "))))
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Node;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, SerdeError> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Node;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, SerdeError> {
+        SerializeSeq::end(self)
+    }
+}
+
+/// Private serializer of map or structure to named nodes
+struct MapSerializer {
+    entries: Vec<(String, Node)>,
+    key: Option<String>,
+}
+
+impl SerializeMap for MapSerializer {
+    type Ok = Node;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        match key.serialize(NodeSerializer)? {
+            Node::Value(Some(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(NodeSerializer::unsupported("map key which is not a value")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self.key.take().ok_or_else(|| SerdeError("value is written before key".to_owned()))?;
+        self.entries.push((key, value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, SerdeError> {
+        Ok(Node::Map(self.entries))
+    }
+}
+
+impl SerializeStruct for MapSerializer {
+    type Ok = Node;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        self.entries.push((key.to_owned(), value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, SerdeError> {
+        Ok(Node::Map(self.entries))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq)]
+    enum Level {
+        Debug,
+        Info,
+    }
+
+    const LEVELS: &[&str] = &["debug", "info"];
+
+    impl Serialize for Level {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Level::Debug => serializer.serialize_unit_variant("Level", 0, LEVELS[0]),
+                Level::Info => serializer.serialize_unit_variant("Level", 1, LEVELS[1]),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Level {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct LevelVisitor;
+
+            impl<'de> Visitor<'de> for LevelVisitor {
+                type Value = Level;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("level")
+                }
+
+                fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Level, A::Error> {
+                    use serde::de::VariantAccess;
+                    let (name, variant) = data.variant::<String>()?;
+                    variant.unit_variant()?;
+                    match name.as_str() {
+                        "debug" => Ok(Level::Debug),
+                        "info" => Ok(Level::Info),
+                        other => Err(de::Error::unknown_variant(other, LEVELS)),
+                    }
+                }
+            }
+
+            deserializer.deserialize_enum("Level", LEVELS, LevelVisitor)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+        targets: Vec<String>,
+        timeout: Option<u32>,
+        level: Level,
+    }
+
+    impl Serialize for Server {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut server = serializer.serialize_struct("Server", 5)?;
+            server.serialize_field("host", &self.host)?;
+            server.serialize_field("port", &self.port)?;
+            server.serialize_field("targets", &self.targets)?;
+            server.serialize_field("timeout", &self.timeout)?;
+            server.serialize_field("level", &self.level)?;
+            server.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Server {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct ServerVisitor;
+
+            impl<'de> Visitor<'de> for ServerVisitor {
+                type Value = Server;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("server")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Server, A::Error> {
+                    let (mut host, mut port, mut targets, mut timeout, mut level) = (None, None, None, None, None);
+                    while let Some(key) = map.next_key::<String>()? {
+                        match key.as_str() {
+                            "host" => host = Some(map.next_value()?),
+                            "port" => port = Some(map.next_value()?),
+                            "targets" => targets = Some(map.next_value()?),
+                            "timeout" => timeout = map.next_value()?,
+                            "level" => level = Some(map.next_value()?),
+                            _ => {
+                                map.next_value::<de::IgnoredAny>()?;
+                            }
+                        }
+                    }
+                    Ok(Server {
+                        host: host.ok_or_else(|| de::Error::missing_field("host"))?,
+                        port: port.ok_or_else(|| de::Error::missing_field("port"))?,
+                        targets: targets.unwrap_or_default(),
+                        timeout,
+                        level: level.ok_or_else(|| de::Error::missing_field("level"))?,
+                    })
+                }
+            }
+
+            deserializer.deserialize_struct("Server", &["host", "port", "targets", "timeout", "level"], ServerVisitor)
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let ini = Ini::new().section("").item("top", 1).section("a").item("b", "c").item_vec("v", &[1, 2]);
+        let copy = to_ini(&ini).unwrap();
+        assert_eq!(copy, ini);
+        assert!(!copy.is_modified());
+        assert_eq!(from_ini::<Ini>(&ini).unwrap(), ini);
+        assert_eq!(from_ini::<Ini>(&Ini::new()).unwrap(), Ini::new());
+    }
+
+    #[test]
+    fn structure() {
+        let input = "[server]\nhost = localhost\nport = 8080\ntargets = file, stderr\ntimeout =\nlevel = info";
+        let config: BTreeMap<String, Server> = from_ini(&Ini::from_string(input).unwrap()).unwrap();
+        let server = Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+            targets: vec!["file".to_owned(), "stderr".to_owned()],
+            timeout: None,
+            level: Level::Info,
+        };
+        assert_eq!(config["server"], server);
+
+        let ini = to_ini(&config).unwrap();
+        assert_eq!(ini.to_string(), "[server]\nhost = localhost\nport = 8080\ntargets = file, stderr\nlevel = info\n");
+        assert_eq!(from_ini::<BTreeMap<String, Server>>(&ini).unwrap(), config);
+
+        let ini = Ini::from_string("top = 1\n[server]\ntargets =\nlevel = debug").unwrap();
+        let sections: BTreeMap<String, BTreeMap<String, Vec<String>>> = from_ini(&ini).unwrap();
+        assert_eq!(sections[""]["top"], ["1"]);
+        assert_eq!(sections["server"]["targets"], Vec::<String>::new());
+        assert!(from_ini::<BTreeMap<String, BTreeMap<String, Level>>>(&ini).is_err());
+        let levels: BTreeMap<String, BTreeMap<String, Level>> =
+            from_ini(&Ini::from_string("[a]\nb = debug").unwrap()).unwrap();
+        assert_eq!(levels["a"]["b"], Level::Debug);
+    }
+
+    #[test]
+    fn errors() {
+        let input = "[server]\nhost = localhost\nport = http\nlevel = info";
+        let error = from_ini::<BTreeMap<String, Server>>(&Ini::from_string(input).unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "invalid value `http` of key `port`");
+        let input = "[server]\nhost = localhost";
+        let error = from_ini::<BTreeMap<String, Server>>(&Ini::from_string(input).unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "missing field `port`");
+
+        let mut nested = BTreeMap::new();
+        nested.insert("a", BTreeMap::new());
+        nested.get_mut("a").unwrap().insert("b", BTreeMap::<&str, u8>::new());
+        assert!(to_ini(&nested).is_err());
+        assert!(to_ini(&[1, 2]).is_err());
+        assert!(to_ini(&BTreeMap::<&str, u8>::new()).unwrap().is_empty());
+        let mut flat = BTreeMap::new();
+        flat.insert("a", 1);
+        assert_eq!(to_ini(&flat).unwrap_err().to_string(), "expected map of keys in section `a`");
+    }
+}
@@ -0,0 +1,294 @@
+//! Value module
+//!
+//! Contains routines to parse human-friendly value formats and value conversion traits for custom formats
+use crate::GetError;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Conversion of text value of key to type, used by all getters of [Ini](crate::Ini) and [Section](crate::Section)
+///
+/// Implemented for all types supporting [FromStr]. Implement it for own types which need non-standard format,
+/// see [IniValue] for example.
+pub trait FromIniValue: Sized {
+    /// Parse value, returning [None] if it has invalid format
+    fn parse_value(value: &str) -> Option<Self>;
+}
+
+/// Conversion of type to text value of key, used by all setters of [Ini](crate::Ini) and [Section](crate::Section)
+///
+/// Implemented for all types supporting [Display](fmt::Display). Implement it for own types which need
+/// non-standard format, see [IniValue] for example.
+pub trait ToIniValue {
+    /// Format value to be written to document
+    fn format_value(&self) -> String;
+}
+
+/// Value which can be both read from and written to document
+///
+/// Implemented for all types supporting [FromIniValue] and [ToIniValue], so only these two traits
+/// need to be implemented for own types. Conversion is split in two traits, because some types can only
+/// be parsed (e.g. [PathBuf](std::path::PathBuf)) or only be written (e.g. [str]).
+///
+/// # Example
+/// ```
+/// # use tini::{FromIniValue, Ini, IniValue, ToIniValue};
+/// #[derive(Debug, PartialEq)]
+/// struct Color(u8, u8, u8);
+///
+/// impl FromIniValue for Color {
+///     fn parse_value(value: &str) -> Option<Self> {
+///         let hex = value.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+///         let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+///         Some(Color(channel(0)?, channel(2)?, channel(4)?))
+///     }
+/// }
+///
+/// impl ToIniValue for Color {
+///     fn format_value(&self) -> String {
+///         format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+///     }
+/// }
+///
+/// fn roundtrip<T: IniValue>(value: T) -> Option<T> {
+///     Ini::new().section("a").item("b", value).get("a", "b")
+/// }
+///
+/// let mut conf = Ini::new().section("theme").item("accent", Color(255, 128, 0)).item("size", 12);
+/// conf.set_vec("theme", "palette", &[Color(0, 0, 0), Color(255, 255, 255)]);
+///
+/// assert_eq!(conf.to_string(), "[theme]\naccent = #ff8000\nsize = 12\npalette = #000000, #ffffff\n");
+/// assert_eq!(conf.get("theme", "accent"), Some(Color(255, 128, 0)));
+/// assert_eq!(conf.get::<u8>("theme", "size"), Some(12));
+/// assert_eq!(conf.get_vec("theme", "palette"), Some(vec![Color(0, 0, 0), Color(255, 255, 255)]));
+/// assert_eq!(roundtrip(Color(1, 2, 3)), Some(Color(1, 2, 3)));
+/// ```
+pub trait IniValue: FromIniValue + ToIniValue {}
+
+impl<T> IniValue for T where T: FromIniValue + ToIniValue {}
+
+impl<T> FromIniValue for T
+where
+    T: FromStr,
+{
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl<T> ToIniValue for T
+where
+    T: fmt::Display + ?Sized,
+{
+    fn format_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// parse boolean written as `true/false`, `yes/no`, `on/off` or `1/0` in any case
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// parse integer with optional sign, `0x`, `0o` or `0b` radix prefix and `_` digit separators
+pub fn parse_int(value: &str) -> Option<i128> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let prefix = value.get(..2).map(|p| p.to_lowercase());
+    let (radix, digits) = match prefix.as_deref() {
+        Some("0x") => (16, &value[2..]),
+        Some("0o") => (8, &value[2..]),
+        Some("0b") => (2, &value[2..]),
+        _ => (10, value),
+    };
+    let digits = digits.trim_start_matches('_');
+    // sign is allowed only before prefix, separators only between digits
+    if digits.is_empty() || digits.starts_with(&['+', '-'][..]) || digits.ends_with('_') {
+        return None;
+    }
+    let magnitude = i128::from_str_radix(&digits.replace('_', ""), radix).ok()?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// parse duration written as sequence of integers with units `d`, `h`, `m`, `s`, `ms`, `us` and `ns`,
+/// plain integer is a number of seconds
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let mut rest = value;
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = rest[digits..].find(|c: char| !c.is_alphabetic() && c != 'µ').map_or(rest.len(), |i| digits + i);
+        let number: u64 = rest[..digits].parse().ok()?;
+        let part = match &rest[digits..unit] {
+            "d" => Duration::from_secs(number.checked_mul(86400)?),
+            "h" => Duration::from_secs(number.checked_mul(3600)?),
+            "m" => Duration::from_secs(number.checked_mul(60)?),
+            "s" => Duration::from_secs(number),
+            "ms" => Duration::from_millis(number),
+            "us" | "µs" => Duration::from_micros(number),
+            "ns" => Duration::from_nanos(number),
+            _ => return None,
+        };
+        total = total.checked_add(part)?;
+        rest = rest[unit..].trim_start();
+    }
+    Some(total).filter(|_| !value.is_empty())
+}
+
+/// convert `raw` value of key telling apart absent key, empty value and invalid value
+pub fn try_parse<T>(raw: Option<&str>) -> Result<T, GetError>
+where
+    T: FromIniValue,
+{
+    match raw {
+        None => Err(GetError::MissingKey),
+        Some("") => Err(GetError::Empty),
+        Some(value) => T::parse_value(value)
+            .ok_or_else(|| GetError::ParseFailed { value: value.to_owned(), type_name: std::any::type_name::<T>() }),
+    }
+}
+
+/// convert `raw` value of key separated by `sep` telling which element is invalid
+pub fn try_parse_vec<T>(raw: Option<&str>, sep: &str) -> Result<Vec<T>, GetError>
+where
+    T: FromIniValue,
+{
+    match raw {
+        None => Err(GetError::MissingKey),
+        Some("") => Err(GetError::Empty),
+        Some(value) => value
+            .split(sep)
+            .map(str::trim)
+            .enumerate()
+            .map(|(index, s)| {
+                T::parse_value(s).ok_or_else(|| GetError::InvalidElement {
+                    index,
+                    value: s.to_owned(),
+                    type_name: std::any::type_name::<T>(),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// parse size in bytes written as number with optional fraction and unit:
+/// `K`, `M`, `G`, `T`, `P`, `E` and `KiB`, `MiB`... are powers of 1024, `KB`, `MB`... are powers of 1000
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let end = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = (&value[..end], value[end..].trim_start().to_lowercase());
+    let (binary, exponent) = match unit.as_str() {
+        "" | "b" => (true, 0),
+        _ => {
+            let exponent = "kmgtpe".find(unit.get(..1)?)? as u32 + 1;
+            match &unit[1..] {
+                "" | "ib" => (true, exponent),
+                "b" => (false, exponent),
+                _ => return None,
+            }
+        }
+    };
+    let multiplier = u128::from(if binary { 1024u64 } else { 1000 }).pow(exponent);
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() || fraction.contains('.') || fraction.len() > 18 {
+        return None;
+    }
+    let whole: u128 = whole.parse().ok()?;
+    let fraction = match fraction {
+        "" => 0,
+        digits => digits.parse::<u128>().ok()? * multiplier / 10u128.pow(digits.len() as u32),
+    };
+    u64::try_from(whole.checked_mul(multiplier)?.checked_add(fraction)?).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bool() {
+        for value in ["true", "Yes", "ON", "1", " on "] {
+            assert_eq!(parse_bool(value), Some(true));
+        }
+        for value in ["false", "NO", "Off", "0"] {
+            assert_eq!(parse_bool(value), Some(false));
+        }
+        for value in ["", "y", "2", "enabled"] {
+            assert_eq!(parse_bool(value), None);
+        }
+    }
+
+    #[test]
+    fn int() {
+        let cases =
+            [("42", 42), ("-1_000", -1000), ("+7", 7), ("0x1F", 31), ("0XfF", 255), ("0o755", 493), ("0b1010", 10)];
+        for (value, expected) in cases {
+            assert_eq!(parse_int(value), Some(expected));
+        }
+        assert_eq!(parse_int("-0x_10"), Some(-16));
+        for value in ["", "0x", "1_", "--1", "0x-1", "0b102", "1.5", "_"] {
+            assert_eq!(parse_int(value), None);
+        }
+    }
+
+    #[test]
+    fn size() {
+        let cases = [
+            ("512", 512),
+            ("10K", 10240),
+            ("10kB", 10000),
+            ("4MiB", 4 << 20),
+            ("4 mib", 4 << 20),
+            ("1.5GB", 1_500_000_000),
+            ("1.5G", 3 << 29),
+            ("15EiB", 15 << 60),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_size(value), Some(expected), "{}", value);
+        }
+        for value in [
+            "16E",
+            "",
+            "K",
+            "1.2.3K",
+            ".5K",
+            "-1K",
+            "10Q",
+            "10KiBs",
+            "1e3",
+            "340282366920938463463374607431768211.999KB",
+        ] {
+            assert_eq!(parse_size(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn duration() {
+        let cases = [
+            ("30", Duration::from_secs(30)),
+            ("500ms", Duration::from_millis(500)),
+            ("2m", Duration::from_secs(120)),
+            ("1h30m", Duration::from_secs(5400)),
+            ("1d 2h 3s", Duration::from_secs(93603)),
+            ("1s 5µs", Duration::new(1, 5000)),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_duration(value), Some(expected));
+        }
+        for value in ["", "-5s", "1.5s", "5 s", "10y", "h", "1h30"] {
+            assert_eq!(parse_duration(value), None, "{}", value);
+        }
+    }
+}
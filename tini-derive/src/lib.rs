@@ -0,0 +1,394 @@
+//! _Derive macros for **tini** structure mapping_
+//!
+//! This crate is used through `derive` feature of [tini](https://docs.rs/tini):
+//! ```toml
+//! [dependencies]
+//! tini = { version = "1.2", features = ["derive"] }
+//! ```
+//!
+//! * [FromSection] and [ToSection] map fields of structure to keys of a section;
+//! * [FromIni] and [ToIni] map fields of structure to sections, so their types must implement
+//!   `FromSection` or `ToSection`.
+//!
+//! Fields of type `Option<T>` may be absent, fields of type `Vec<T>` are read and written as comma-separated lists.
+//!
+//! # Attributes
+//!
+//! * `#[ini(rename = "name")]` &mdash; use `name` as key or section name instead of field name;
+//! * `#[ini(default)]` &mdash; use [Default] value if key or section is absent;
+//! * `#[ini(default = "path")]` &mdash; call function `path` to get value if key or section is absent.
+//!
+//! # Example
+//! ```
+//! use tini::{FromIni, FromSection, Ini, ToIni, ToSection};
+//!
+//! #[derive(FromSection, ToSection)]
+//! struct Server {
+//!     host: String,
+//!     #[ini(default = "default_port")]
+//!     port: u16,
+//!     aliases: Vec<String>,
+//! }
+//!
+//! #[derive(FromSection, ToSection, Default)]
+//! struct Log {
+//!     #[ini(rename = "log-level")]
+//!     level: Option<String>,
+//! }
+//!
+//! #[derive(FromIni, ToIni)]
+//! struct Config {
+//!     server: Server,
+//!     #[ini(default)]
+//!     log: Log,
+//! }
+//!
+//! fn default_port() -> u16 {
+//!     80
+//! }
+//!
+//! let conf = Ini::from_string("[server]\nhost = localhost\naliases = a, b").unwrap();
+//! let config = Config::from_ini(&conf).unwrap();
+//! assert_eq!(config.server.port, 80);
+//! assert_eq!(config.server.aliases, ["a", "b"]);
+//! assert_eq!(config.log.level, None);
+//!
+//! let conf = config.to_ini();
+//! assert_eq!(conf.to_string(), "[server]\nhost = localhost\nport = 80\naliases = a, b\n\n[log]\n");
+//! ```
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Derive `tini::FromSection` for structure with named fields
+#[proc_macro_derive(FromSection, attributes(ini))]
+pub fn derive_from_section(input: TokenStream) -> TokenStream {
+    expand(input, from_section)
+}
+
+/// Derive `tini::ToSection` for structure with named fields
+#[proc_macro_derive(ToSection, attributes(ini))]
+pub fn derive_to_section(input: TokenStream) -> TokenStream {
+    expand(input, to_section)
+}
+
+/// Derive `tini::FromIni` for structure with named fields
+#[proc_macro_derive(FromIni, attributes(ini))]
+pub fn derive_from_ini(input: TokenStream) -> TokenStream {
+    expand(input, from_ini)
+}
+
+/// Derive `tini::ToIni` for structure with named fields
+#[proc_macro_derive(ToIni, attributes(ini))]
+pub fn derive_to_ini(input: TokenStream) -> TokenStream {
+    expand(input, to_ini)
+}
+
+/// Kind of field type which changes the way it is read and written
+#[derive(Clone, Copy, PartialEq)]
+enum Wrapper {
+    Plain,
+    Option,
+    Vec,
+    OptionVec,
+}
+
+/// Value used for absent key or section
+enum DefaultValue {
+    Required,
+    Trait,
+    Function(String),
+}
+
+struct Field {
+    /// field name as written in structure
+    ident: String,
+    /// key or section name
+    name: String,
+    default: DefaultValue,
+    wrapper: Wrapper,
+}
+
+struct Structure {
+    name: String,
+    fields: Vec<Field>,
+}
+
+fn expand(input: TokenStream, generate: fn(&Structure) -> String) -> TokenStream {
+    let code = match parse_structure(input) {
+        Ok(structure) => generate(&structure),
+        Err(message) => format!("::std::compile_error!({:?});", message),
+    };
+    code.parse().expect("generated code is valid")
+}
+
+fn from_section(structure: &Structure) -> String {
+    let fields: Vec<String> = structure
+        .fields
+        .iter()
+        .map(|field| {
+            let getter = match field.wrapper {
+                Wrapper::Vec | Wrapper::OptionVec => "vec",
+                Wrapper::Plain | Wrapper::Option => "value",
+            };
+            let value = format!("::tini::__private::{}(section, {:?})?", getter, field.name);
+            let missing = format!("::tini::__private::missing_key({:?})", field.name);
+            format!("{}: {}", field.ident, unwrap(field, value, missing))
+        })
+        .collect();
+    format!(
+        "impl ::tini::FromSection for {} {{
+            fn from_section(section: &::tini::Section) -> ::std::result::Result<Self, ::tini::MappingError> {{
+                ::std::result::Result::Ok({} {{ {} }})
+            }}
+        }}",
+        structure.name,
+        structure.name,
+        fields.join(", ")
+    )
+}
+
+fn to_section(structure: &Structure) -> String {
+    let fields: String = structure
+        .fields
+        .iter()
+        .map(|field| match field.wrapper {
+            Wrapper::Plain => format!("section.insert({:?}, &self.{});", field.name, field.ident),
+            Wrapper::Vec => {
+                format!("section.insert({:?}, ::tini::__private::join(&self.{}));", field.name, field.ident)
+            }
+            Wrapper::Option => format!(
+                "if let ::std::option::Option::Some(value) = &self.{} {{ section.insert({:?}, value); }}",
+                field.ident, field.name
+            ),
+            Wrapper::OptionVec => format!(
+                "if let ::std::option::Option::Some(value) = &self.{} {{
+                    section.insert({:?}, ::tini::__private::join(value));
+                }}",
+                field.ident, field.name
+            ),
+        })
+        .collect();
+    format!(
+        "impl ::tini::ToSection for {} {{
+            fn to_section(&self) -> ::tini::Section {{
+                let mut section = ::tini::Section::new();
+                {}
+                section
+            }}
+        }}",
+        structure.name, fields
+    )
+}
+
+fn from_ini(structure: &Structure) -> String {
+    let fields: Vec<String> = structure
+        .fields
+        .iter()
+        .map(|field| {
+            let value = format!("::tini::__private::section(ini, {:?})?", field.name);
+            let missing = format!("::tini::__private::missing_section({:?})", field.name);
+            format!("{}: {}", field.ident, unwrap(field, value, missing))
+        })
+        .collect();
+    format!(
+        "impl ::tini::FromIni for {} {{
+            fn from_ini(ini: &::tini::Ini) -> ::std::result::Result<Self, ::tini::MappingError> {{
+                ::std::result::Result::Ok({} {{ {} }})
+            }}
+        }}",
+        structure.name,
+        structure.name,
+        fields.join(", ")
+    )
+}
+
+fn to_ini(structure: &Structure) -> String {
+    let fields: String = structure
+        .fields
+        .iter()
+        .map(|field| match field.wrapper {
+            Wrapper::Option | Wrapper::OptionVec => format!(
+                "if let ::std::option::Option::Some(value) = &self.{} {{
+                    ::tini::__private::insert_section(&mut ini, {:?}, ::tini::ToSection::to_section(value));
+                }}",
+                field.ident, field.name
+            ),
+            Wrapper::Plain | Wrapper::Vec => format!(
+                "::tini::__private::insert_section(&mut ini, {:?}, ::tini::ToSection::to_section(&self.{}));",
+                field.name, field.ident
+            ),
+        })
+        .collect();
+    format!(
+        "impl ::tini::ToIni for {} {{
+            fn to_ini(&self) -> ::tini::Ini {{
+                let mut ini = ::tini::Ini::new();
+                {}
+                ini
+            }}
+        }}",
+        structure.name, fields
+    )
+}
+
+/// Turn `Option` returned by getter `value` into field value
+fn unwrap(field: &Field, value: String, missing: String) -> String {
+    if let Wrapper::Option | Wrapper::OptionVec = field.wrapper {
+        return value;
+    }
+    match &field.default {
+        DefaultValue::Required => format!("{}.ok_or_else(|| {})?", value, missing),
+        DefaultValue::Trait => format!("{}.unwrap_or_default()", value),
+        DefaultValue::Function(path) => format!("{}.unwrap_or_else({})", value, path),
+    }
+}
+
+fn parse_structure(input: TokenStream) -> Result<Structure, String> {
+    let mut tokens = input.into_iter();
+    // skip attributes and visibility, they don't contain bare `struct` keyword
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => break,
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "enum" || ident.to_string() == "union" => {
+                return Err("only structures with named fields are supported".to_owned())
+            }
+            Some(_) => {}
+            None => return Err("structure is expected".to_owned()),
+        }
+    }
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("structure name is expected".to_owned()),
+    };
+    match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            let fields = split(group.stream().into_iter().collect(), ',')
+                .into_iter()
+                .filter(|tokens| !tokens.is_empty())
+                .map(parse_field)
+                .collect::<Result<_, _>>()?;
+            Ok(Structure { name, fields })
+        }
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            Err("generic structures are not supported".to_owned())
+        }
+        _ => Err("only structures with named fields are supported".to_owned()),
+    }
+}
+
+fn parse_field(tokens: Vec<TokenTree>) -> Result<Field, String> {
+    let mut field =
+        Field { ident: String::new(), name: String::new(), default: DefaultValue::Required, wrapper: Wrapper::Plain };
+    let mut rename = None;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = tokens.next() {
+                    parse_attribute(group.stream(), &mut field, &mut rename)?;
+                }
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "pub" => {
+                // skip restriction like `pub(crate)`
+                if let Some(TokenTree::Group(group)) = tokens.peek() {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        tokens.next();
+                    }
+                }
+            }
+            TokenTree::Ident(ident) => {
+                field.ident = ident.to_string();
+                break;
+            }
+            _ => return Err("field name is expected".to_owned()),
+        }
+    }
+    match tokens.next() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {}
+        _ => return Err("only structures with named fields are supported".to_owned()),
+    }
+    let ty: Vec<TokenTree> = tokens.collect();
+    field.wrapper = match wrapper(&ty) {
+        Some(("Option", inner)) if wrapper(&inner).map(|(name, _)| name) == Some("Vec") => Wrapper::OptionVec,
+        Some(("Option", _)) => Wrapper::Option,
+        Some(("Vec", _)) => Wrapper::Vec,
+        _ => Wrapper::Plain,
+    };
+    if let (Wrapper::Option | Wrapper::OptionVec, DefaultValue::Trait | DefaultValue::Function(_)) =
+        (field.wrapper, &field.default)
+    {
+        return Err(format!("`Option` field `{}` is already optional, `default` is not needed", field.ident));
+    }
+    field.name = rename.unwrap_or_else(|| field.ident.trim_start_matches("r#").to_owned());
+    Ok(field)
+}
+
+/// Parse `ini(...)` attribute of field, other attributes are ignored
+fn parse_attribute(stream: TokenStream, field: &mut Field, rename: &mut Option<String>) -> Result<(), String> {
+    let mut tokens = stream.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(group))) if ident.to_string() == "ini" => {
+            for item in split(group.stream().into_iter().collect(), ',') {
+                match item.as_slice() {
+                    [] => {}
+                    [TokenTree::Ident(key)] if key.to_string() == "default" => field.default = DefaultValue::Trait,
+                    [TokenTree::Ident(key), TokenTree::Punct(eq), value] if eq.as_char() == '=' => {
+                        let value = string_literal(value)?;
+                        match key.to_string().as_str() {
+                            "rename" => *rename = Some(value),
+                            "default" => field.default = DefaultValue::Function(value),
+                            key => return Err(format!("unknown attribute `{}`", key)),
+                        }
+                    }
+                    _ => return Err("attribute `ini(rename = \"...\")` or `ini(default)` is expected".to_owned()),
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn string_literal(token: &TokenTree) -> Result<String, String> {
+    let text = token.to_string();
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        Ok(text[1..text.len() - 1].to_owned())
+    } else {
+        Err(format!("string literal is expected instead of `{}`", text))
+    }
+}
+
+/// Name of outer type and tokens of its generic argument, e.g. `Option` and `u8` for `std::option::Option<u8>`
+fn wrapper(ty: &[TokenTree]) -> Option<(&'static str, Vec<TokenTree>)> {
+    let open = ty.iter().position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == '<'))?;
+    let name = match ty[..open].last()? {
+        TokenTree::Ident(ident) if ident.to_string() == "Option" => "Option",
+        TokenTree::Ident(ident) if ident.to_string() == "Vec" => "Vec",
+        _ => return None,
+    };
+    let close = ty.iter().rposition(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == '>'))?;
+    Some((name, ty[open + 1..close].to_vec()))
+}
+
+/// Split tokens by `separator` outside of angle brackets
+fn split(tokens: Vec<TokenTree>, separator: char) -> Vec<Vec<TokenTree>> {
+    let mut result = vec![Vec::new()];
+    let mut depth = 0;
+    let mut arrow = false;
+    for token in tokens {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                // `>` of `->` doesn't close angle bracket
+                '>' if !arrow && depth > 0 => depth -= 1,
+                c if c == separator && depth == 0 => {
+                    result.push(Vec::new());
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        arrow = matches!(&token, TokenTree::Punct(p) if p.as_char() == '-');
+        result.last_mut().expect("result is not empty").push(token);
+    }
+    result
+}
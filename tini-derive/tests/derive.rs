@@ -0,0 +1,85 @@
+use tini::{FromIni, FromSection, GetError, Ini, MappingError, ToIni, ToSection};
+
+#[derive(Debug, PartialEq, FromSection, ToSection)]
+struct Server {
+    host: String,
+    #[ini(rename = "listen-port")]
+    port: u16,
+    #[ini(default)]
+    workers: u8,
+    tags: Vec<String>,
+    backup: Option<String>,
+    weights: Option<Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq, Default, FromSection, ToSection)]
+struct Log {
+    #[ini(default = "default_level")]
+    pub(crate) level: String,
+    r#type: Option<String>,
+}
+
+#[derive(Debug, PartialEq, FromIni, ToIni)]
+struct Config {
+    server: Server,
+    #[ini(default)]
+    log: Log,
+    #[ini(rename = "fallback server")]
+    fallback: Option<Server>,
+}
+
+fn default_level() -> String {
+    "info".to_owned()
+}
+
+#[test]
+fn roundtrip() {
+    let conf = Ini::from_string(
+        "[server]\nhost = localhost\nlisten-port = 8080\ntags = a, b\nweights = 1, 2\n\n[log]\ntype = file",
+    )
+    .unwrap();
+    let config = Config::from_ini(&conf).unwrap();
+    assert_eq!(
+        config.server,
+        Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+            workers: 0,
+            tags: vec!["a".to_owned(), "b".to_owned()],
+            backup: None,
+            weights: Some(vec![1, 2]),
+        }
+    );
+    assert_eq!(config.log, Log { level: "info".to_owned(), r#type: Some("file".to_owned()) });
+    assert_eq!(config.fallback, None);
+
+    let output = config.to_ini();
+    assert_eq!(
+        output.to_string(),
+        "[server]\nhost = localhost\nlisten-port = 8080\nworkers = 0\ntags = a, b\nweights = 1, 2\n\n\
+         [log]\nlevel = info\ntype = file\n"
+    );
+    assert_eq!(Config::from_ini(&output).unwrap(), config);
+}
+
+#[test]
+fn errors() {
+    let error = Config::from_ini(&Ini::new()).unwrap_err();
+    assert_eq!(error, MappingError::section("server", GetError::MissingSection));
+
+    let conf = Ini::from_string("[server]\nhost = localhost\ntags =").unwrap();
+    let error = Config::from_ini(&conf).unwrap_err();
+    assert_eq!(error, MappingError::key("server", "listen-port", GetError::MissingKey));
+    assert_eq!(error.to_string(), "Key is not set: key `listen-port` in section `server`");
+
+    let conf = Ini::from_string("[server]\nhost = localhost\nlisten-port = 80\ntags =\nweights = 1, x").unwrap();
+    let error = Config::from_ini(&conf).unwrap_err();
+    assert_eq!(
+        error,
+        MappingError::key(
+            "server",
+            "weights",
+            GetError::InvalidElement { index: 1, value: "x".to_owned(), type_name: "u8" }
+        )
+    );
+}